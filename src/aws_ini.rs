@@ -0,0 +1,605 @@
+use std::collections::HashMap;
+use std::fmt;
+
+pub type Properties = HashMap<String, String>;
+
+// line and column are 1-based, text is the offending line as it appears in the file
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub text: String,
+    pub message: String,
+    pub hint: String,
+}
+
+impl ParseError {
+    fn new(index: usize, column: usize, raw: &str, message: String, hint: &str) -> ParseError {
+        ParseError { line: index + 1, column, text: raw.to_string(), message, hint: hint.to_string() }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+// column of the first non-whitespace character of a line
+fn first_column(raw: &str) -> usize {
+    raw.chars().take_while(|c| c.is_whitespace()).count() + 1
+}
+
+// every line of the original file is kept verbatim so that writing the file back
+// only changes the lines that have been set or deleted
+#[derive(Clone, Debug)]
+enum Line {
+    Property { key: String, raw: String },
+    // indented line under a property, e.g. `max_concurrent_requests = 20` under `s3 =`
+    Nested { parent: String, raw: String },
+    // blank line or comment
+    Other { raw: String },
+}
+
+#[derive(Clone, Debug)]
+struct Section {
+    name: Option<String>,
+    header: Option<String>,
+    properties: Properties,
+    // properties of nested blocks keyed by the top-level property they belong to, e.g. `s3`
+    nested: HashMap<String, Properties>,
+    lines: Vec<Line>,
+}
+
+impl Section {
+    fn new(name: Option<String>, header: Option<String>) -> Section {
+        Section { name, header, properties: Properties::new(), nested: HashMap::new(), lines: vec!() }
+    }
+
+    fn find_property_line(&self, key: &str) -> Option<usize> {
+        self.lines.iter().position(|line| matches!(line, Line::Property { key: line_key, .. } if line_key == key))
+    }
+
+    // new properties go after the last property of the section, before trailing blank lines and comments
+    fn insert_position(&self) -> usize {
+        self.lines.iter()
+            .rposition(|line| !matches!(line, Line::Other { .. }))
+            .map_or(0, |index| index + 1)
+    }
+
+    // continuation lines and nested block of the property at the given line index
+    fn remove_nested_lines(&mut self, index: usize, key: &str) {
+        while index + 1 < self.lines.len() {
+            match self.lines[index + 1] {
+                Line::Nested { ref parent, .. } if parent == key => { self.lines.remove(index + 1); },
+                _ => break
+            }
+        }
+        self.nested.remove(key);
+    }
+
+    // the new value replaces the whole old value, including its continuation lines or nested block
+    fn set(&mut self, key: String, value: String) {
+        match self.find_property_line(&key) {
+            Some(index) => {
+                if let Line::Property { ref mut raw, .. } = self.lines[index] {
+                    *raw = replace_value(raw, &value);
+                }
+                self.remove_nested_lines(index, &key);
+            },
+            None => {
+                let position = self.insert_position();
+                self.lines.insert(position, Line::Property { key: key.clone(), raw: format!("{} = {}", key, value) });
+            }
+        }
+        self.properties.insert(key, value);
+    }
+
+    fn delete(&mut self, key: &str) -> Option<String> {
+        if let Some(index) = self.find_property_line(key) {
+            self.remove_nested_lines(index, key);
+            self.lines.remove(index);
+        }
+        self.properties.remove(key)
+    }
+}
+
+fn replace_value(raw: &str, value: &str) -> String {
+    match raw.find('=') {
+        Some(index) => {
+            let (key_part, value_part) = raw.split_at(index + 1);
+            let spacing: String = value_part.chars().take_while(|c| c.is_whitespace()).collect();
+            // `key =` without value gets the same spacing after `=` as before it
+            let spacing = if spacing.is_empty() && key_part[..index].ends_with(char::is_whitespace) {
+                " ".to_string()
+            } else {
+                spacing
+            };
+            format!("{}{}{}", key_part, spacing, value)
+        },
+        None => format!("{} = {}", raw.trim_end(), value)
+    }
+}
+
+fn parse_key_value(line: &str) -> Option<(String, String)> {
+    line.find('=').map(|index| {
+        let (key, value) = line.split_at(index);
+        (key.trim().to_string(), value[1..].trim().to_string())
+    })
+}
+
+pub struct SectionSetter<'a> {
+    ini: &'a mut Ini,
+    section: Option<String>,
+}
+
+impl<'a> SectionSetter<'a> {
+    pub fn set<K: Into<String>, V: Into<String>>(&mut self, key: K, value: V) -> &mut SectionSetter<'a> {
+        self.ini.set_to(self.section.clone(), key.into(), value.into());
+        self
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Ini {
+    sections: Vec<Section>,
+    newline: String,
+    // a file without newline at the end is written back without it
+    final_newline: bool,
+}
+
+impl Default for Ini {
+    fn default() -> Self {
+        Ini::new()
+    }
+}
+
+impl Ini {
+    pub fn new() -> Ini {
+        Ini { sections: vec!(Section::new(None, None)), newline: "\n".to_string(), final_newline: true }
+    }
+
+    pub fn load_from_str(content: &str) -> Result<Ini, ParseError> {
+        let mut ini = Ini::new();
+        if content.contains("\r\n") {
+            ini.newline = "\r\n".to_string();
+        }
+        ini.final_newline = content.is_empty() || content.ends_with('\n');
+
+        // property that following lines indented deeper than it belong to, with its column
+        // and whether it starts a nested block
+        let mut parent: Option<(String, bool, usize)> = None;
+        for (index, raw) in content.lines().enumerate() {
+            let trimmed = raw.trim();
+            let section = ini.sections.last_mut().unwrap();
+            let column = first_column(raw);
+            let nested_under = parent.clone().filter(|(_, _, parent_column)| column > *parent_column);
+
+            if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with(';') {
+                section.lines.push(Line::Other { raw: raw.to_string() });
+            } else if trimmed.starts_with('[') {
+                if !trimmed.ends_with(']') {
+                    return Err(ParseError::new(index, raw.trim_end().chars().count() + 1, raw,
+                                               "expecting \"]\" at the end of section header".to_string(),
+                                               "section headers look like \"[default]\" or \"[profile name]\""));
+                }
+                let name = trimmed[1..trimmed.len() - 1].trim().to_string();
+                ini.sections.push(Section::new(Some(name), Some(raw.to_string())));
+                parent = None;
+            } else if let Some((parent_key, is_nested_block, _)) = nested_under {
+                if is_nested_block {
+                    match parse_key_value(trimmed) {
+                        Some((key, value)) => {
+                            section.nested.entry(parent_key.clone()).or_default().insert(key, value);
+                        },
+                        None => return Err(ParseError::new(index, column, raw,
+                                                           format!("expecting \"key = value\" under [{}]", parent_key),
+                                                           "indented lines under a property without value must be \"key = value\""))
+                    }
+                }
+                section.lines.push(Line::Nested { parent: parent_key, raw: raw.to_string() });
+            } else {
+                match parse_key_value(trimmed) {
+                    Some((key, value)) => {
+                        parent = Some((key.clone(), value.is_empty(), column));
+                        section.properties.insert(key.clone(), value);
+                        section.lines.push(Line::Property { key, raw: raw.to_string() });
+                    },
+                    None => return Err(ParseError::new(index, column, raw,
+                                                       "expecting \"key = value\" or \"[section]\"".to_string(),
+                                                       "comments must start with \"#\" or \";\", continuation lines must be indented"))
+                }
+            }
+        }
+
+        Ok(ini)
+    }
+
+    fn find_section(&self, name: &Option<String>) -> Option<&Section> {
+        self.sections.iter().find(|section| &section.name == name)
+    }
+
+    fn find_section_mut(&mut self, name: &Option<String>) -> Option<&mut Section> {
+        self.sections.iter_mut().find(|section| &section.name == name)
+    }
+
+    // a new section is separated from the previous one by a blank line
+    fn needs_separator(&self) -> bool {
+        match self.sections.last() {
+            Some(section) => match section.lines.last() {
+                Some(Line::Other { raw }) => !raw.trim().is_empty(),
+                Some(_) => true,
+                None => section.header.is_some()
+            },
+            None => false
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Option<String>, &Properties)> {
+        self.sections.iter()
+            .filter(|section| section.name.is_some() || !section.properties.is_empty())
+            .map(|section| (&section.name, &section.properties))
+    }
+
+    pub fn section<S: Into<String>>(&self, name: Option<S>) -> Option<&Properties> {
+        self.find_section(&name.map(|s| s.into())).map(|section| &section.properties)
+    }
+
+    pub fn get_from<S: Into<String>>(&self, section: Option<S>, key: &str) -> Option<&str> {
+        self.section(section)
+            .and_then(|properties| properties.get(key))
+            .map(|value| value.as_str())
+    }
+
+    pub fn get_nested_from<S: Into<String>>(&self, section: Option<S>, key: &str) -> Option<&Properties> {
+        self.find_section(&section.map(|s| s.into()))
+            .and_then(|section| section.nested.get(key))
+    }
+
+    pub fn with_section<S: Into<String>>(&mut self, section: Option<S>) -> SectionSetter<'_> {
+        SectionSetter { ini: self, section: section.map(|s| s.into()) }
+    }
+
+    pub fn set_to<S: Into<String>>(&mut self, section: Option<S>, key: String, value: String) {
+        let name = section.map(|s| s.into());
+        if self.find_section(&name).is_none() {
+            if self.needs_separator() {
+                self.sections.last_mut().unwrap().lines.push(Line::Other { raw: String::new() });
+            }
+            let header = name.as_ref().map(|n| format!("[{}]", n));
+            self.sections.push(Section::new(name.clone(), header));
+        }
+
+        self.find_section_mut(&name).unwrap().set(key, value);
+    }
+
+    pub fn delete_from<S: Into<String>>(&mut self, section: Option<S>, key: &str) -> Option<String> {
+        self.find_section_mut(&section.map(|s| s.into()))
+            .and_then(|section| section.delete(key))
+    }
+
+    // comments at the end of a section usually describe the next one, so comments right before the deleted
+    // section are deleted with it while its own trailing comments are moved to the previous section,
+    // the separator of the last section is deleted with it
+    pub fn delete_section<S: Into<String>>(&mut self, section: S) -> Option<Properties> {
+        let name = Some(section.into());
+        let index = self.sections.iter().position(|section| section.name == name)?;
+        let deleted = self.sections.remove(index);
+
+        let previous_lines = &mut self.sections[index - 1].lines;
+        while matches!(previous_lines.last(), Some(Line::Other { raw }) if !raw.trim().is_empty()) {
+            previous_lines.pop();
+        }
+        let comments: Vec<Line> = deleted.lines[deleted.insert_position()..].iter()
+            .skip_while(|line| matches!(line, Line::Other { raw } if raw.trim().is_empty()))
+            .cloned()
+            .collect();
+        let is_last = index == self.sections.len();
+        let previous = &mut self.sections[index - 1];
+        previous.lines.extend(comments);
+        if is_last {
+            while matches!(previous.lines.last(), Some(Line::Other { raw }) if raw.trim().is_empty()) {
+                previous.lines.pop();
+            }
+        }
+        Some(deleted.properties)
+    }
+
+    // returns false if the section is not found
+    pub fn rename_section<S: Into<String>>(&mut self, section: S, new_name: S) -> bool {
+        let new_name = new_name.into();
+        match self.find_section_mut(&Some(section.into())) {
+            Some(section) => {
+                section.header = Some(format!("[{}]", new_name));
+                section.name = Some(new_name);
+                true
+            },
+            None => false
+        }
+    }
+}
+
+impl fmt::Display for Ini {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut raws = vec!();
+        for section in &self.sections {
+            raws.extend(section.header.iter());
+            raws.extend(section.lines.iter().map(|line| match line {
+                Line::Property { raw, .. } | Line::Nested { raw, .. } | Line::Other { raw } => raw,
+            }));
+        }
+        write!(f, "{}", raws.iter().map(|raw| raw.as_str()).collect::<Vec<&str>>().join(&self.newline))?;
+        if self.final_newline && !raws.is_empty() {
+            write!(f, "{}", self.newline)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    const CONFIG: &str = "# shared settings\n\
+                          [default]\n\
+                          region=us-east-1\n\
+                          role_arn =\n\
+                          \n\
+                          ; assumed profile\n\
+                          [profile first]\n\
+                          role_arn   =   arn_1\n\
+                          s3 =\n  max_concurrent_requests = 20\n  addressing_style = path\n\
+                          source_profile = a\n\
+                          \n";
+
+    mod load_from_str {
+        use aws_ini::Ini;
+
+        #[test]
+        fn parse_sections_and_top_level_properties() {
+            let ini = Ini::load_from_str(super::CONFIG).unwrap();
+
+            assert_eq!(Some("us-east-1"), ini.get_from(Some("default"), "region"));
+            assert_eq!(Some(""), ini.get_from(Some("default"), "role_arn"));
+            assert_eq!(Some("arn_1"), ini.get_from(Some("profile first"), "role_arn"));
+            assert_eq!(Some("a"), ini.get_from(Some("profile first"), "source_profile"));
+            assert_eq!(None, ini.get_from(Some("profile first"), "max_concurrent_requests"));
+        }
+
+        #[test]
+        fn parse_nested_blocks_separately_from_top_level_properties() {
+            let ini = Ini::load_from_str(super::CONFIG).unwrap();
+
+            let s3 = ini.get_nested_from(Some("profile first"), "s3").unwrap();
+            assert_eq!(2, s3.len());
+            assert_eq!(Some(&"20".to_string()), s3.get("max_concurrent_requests"));
+            assert_eq!(Some(&"path".to_string()), s3.get("addressing_style"));
+            assert_eq!(Some(""), ini.get_from(Some("profile first"), "s3"));
+            assert!(ini.get_nested_from(Some("profile first"), "role_arn").is_none());
+        }
+
+        #[test]
+        fn keep_indented_lines_under_property_with_value_as_continuation() {
+            let ini = Ini::load_from_str("[default]\nca_bundle = a\n  b\nregion = c\n").unwrap();
+
+            assert!(ini.get_nested_from(Some("default"), "ca_bundle").is_none());
+            assert_eq!(Some("c"), ini.get_from(Some("default"), "region"));
+            assert_eq!("[default]\nca_bundle = a\n  b\nregion = c\n", ini.to_string());
+        }
+
+        #[test]
+        fn parse_uniformly_indented_keys_as_properties() {
+            let content = "[profile a]\n  role_arn = arn_1\n  source_profile = s\n  s3 =\n    addressing_style = path\n";
+            let ini = Ini::load_from_str(content).unwrap();
+
+            assert_eq!(Some("arn_1"), ini.get_from(Some("profile a"), "role_arn"));
+            assert_eq!(Some("s"), ini.get_from(Some("profile a"), "source_profile"));
+            assert_eq!(Some("path"), ini.get_nested_from(Some("profile a"), "s3").and_then(|s3| s3.get("addressing_style")).map(|style| style.as_str()));
+            assert_eq!(content, ini.to_string());
+        }
+
+        #[test]
+        fn parse_indented_section_header_after_property() {
+            let ini = Ini::load_from_str("[profile a]\nregion = a\n  [profile b]\n  region = b\n").unwrap();
+
+            assert_eq!(Some("a"), ini.get_from(Some("profile a"), "region"));
+            assert_eq!(Some("b"), ini.get_from(Some("profile b"), "region"));
+        }
+
+        #[test]
+        fn return_error_if_nested_line_is_not_key_value() {
+            let result = Ini::load_from_str("[default]\ns3 =\n  not a key value\n");
+
+            assert_eq!(3, result.unwrap_err().line);
+        }
+
+        #[test]
+        fn return_error_with_line_number_if_line_is_not_key_value() {
+            let result = Ini::load_from_str("[default]\nregion = a\nnot a key value\n");
+
+            let error = result.unwrap_err();
+            assert_eq!(3, error.line);
+            assert_eq!(1, error.column);
+            assert_eq!("not a key value", error.text);
+        }
+
+        #[test]
+        fn return_error_if_section_header_is_not_closed() {
+            let result = Ini::load_from_str("[default\n");
+
+            let error = result.unwrap_err();
+            assert_eq!(1, error.line);
+            assert_eq!(9, error.column);
+            assert!(!error.hint.is_empty());
+        }
+    }
+
+    mod to_string {
+        use aws_ini::Ini;
+
+        #[test]
+        fn write_back_unchanged_file_verbatim() {
+            let ini = Ini::load_from_str(super::CONFIG).unwrap();
+
+            assert_eq!(super::CONFIG, ini.to_string());
+        }
+
+        #[test]
+        fn keep_crlf_line_endings() {
+            let content = "[default]\r\nregion = a\r\n";
+            let mut ini = Ini::load_from_str(content).unwrap();
+            ini.set_to(Some("default"), "region".to_string(), "b".to_string());
+
+            assert_eq!("[default]\r\nregion = b\r\n", ini.to_string());
+        }
+
+        #[test]
+        fn keep_missing_newline_at_the_end_of_file() {
+            let content = "[default]\nregion = a";
+            let mut ini = Ini::load_from_str(content).unwrap();
+
+            assert_eq!(content, ini.to_string());
+            ini.set_to(Some("default"), "region".to_string(), "b".to_string());
+            assert_eq!("[default]\nregion = b", ini.to_string());
+        }
+    }
+
+    mod set_to {
+        use aws_ini::Ini;
+
+        #[test]
+        fn replace_value_of_existing_key_keeping_its_formatting() {
+            let mut ini = Ini::load_from_str(super::CONFIG).unwrap();
+            ini.set_to(Some("default"), "region".to_string(), "ap-southeast-2".to_string());
+            ini.set_to(Some("default"), "role_arn".to_string(), "arn_2".to_string());
+            ini.set_to(Some("profile first"), "role_arn".to_string(), "arn_3".to_string());
+
+            let expected = super::CONFIG.replace("region=us-east-1", "region=ap-southeast-2")
+                                        .replace("role_arn =\n", "role_arn = arn_2\n")
+                                        .replace("role_arn   =   arn_1", "role_arn   =   arn_3");
+            assert_eq!(expected, ini.to_string());
+            assert_eq!(Some("arn_2"), ini.get_from(Some("default"), "role_arn"));
+        }
+
+        #[test]
+        fn add_new_key_after_last_property_of_section() {
+            let mut ini = Ini::load_from_str(super::CONFIG).unwrap();
+            ini.set_to(Some("default"), "output".to_string(), "json".to_string());
+
+            let expected = super::CONFIG.replace("role_arn =\n", "role_arn =\noutput = json\n");
+            assert_eq!(expected, ini.to_string());
+        }
+
+        #[test]
+        fn add_new_section_at_the_end_of_file() {
+            let mut ini = Ini::load_from_str("[first]\na = 1\n").unwrap();
+            ini.set_to(Some("default"), "b".to_string(), "2".to_string());
+
+            assert_eq!("[first]\na = 1\n\n[default]\nb = 2\n", ini.to_string());
+        }
+
+        #[test]
+        fn create_section_in_empty_file() {
+            let mut ini = Ini::new();
+            ini.with_section(Some("default")).set("a", "1").set("b", "2");
+
+            assert_eq!("[default]\na = 1\nb = 2\n", ini.to_string());
+        }
+
+        #[test]
+        fn replace_continuation_lines_and_nested_block_of_existing_key() {
+            let mut ini = Ini::load_from_str("[default]\nca_bundle = a\n  b\ns3 =\n  addressing_style = path\nregion = c\n").unwrap();
+            ini.set_to(Some("default"), "ca_bundle".to_string(), "d".to_string());
+            ini.set_to(Some("default"), "s3".to_string(), "e".to_string());
+
+            assert_eq!("[default]\nca_bundle = d\ns3 = e\nregion = c\n", ini.to_string());
+            assert!(ini.get_nested_from(Some("default"), "s3").is_none());
+        }
+    }
+
+    mod delete_from {
+        use aws_ini::Ini;
+
+        #[test]
+        fn remove_key_line_and_its_nested_lines() {
+            let mut ini = Ini::load_from_str(super::CONFIG).unwrap();
+            let removed_s3 = ini.delete_from(Some("profile first"), "s3");
+            let removed_role_arn = ini.delete_from(Some("default"), "role_arn");
+
+            let expected = super::CONFIG.replace("s3 =\n  max_concurrent_requests = 20\n  addressing_style = path\n", "")
+                                        .replace("role_arn =\n", "");
+            assert_eq!(expected, ini.to_string());
+            assert_eq!(Some("".to_string()), removed_s3);
+            assert_eq!(Some("".to_string()), removed_role_arn);
+            assert_eq!(None, ini.get_from(Some("default"), "role_arn"));
+            assert!(ini.get_nested_from(Some("profile first"), "s3").is_none());
+        }
+
+        #[test]
+        fn return_none_if_key_not_found() {
+            let mut ini = Ini::load_from_str(super::CONFIG).unwrap();
+
+            assert_eq!(None, ini.delete_from(Some("default"), "not_existing"));
+            assert_eq!(super::CONFIG, ini.to_string());
+        }
+    }
+
+    mod delete_section {
+        use aws_ini::Ini;
+
+        #[test]
+        fn remove_section_with_its_comment_keeping_comment_of_next_section() {
+            let mut ini = Ini::load_from_str(super::CONFIG).unwrap();
+            let removed = ini.delete_section("default");
+
+            let expected = super::CONFIG.replace("# shared settings\n[default]\nregion=us-east-1\nrole_arn =\n\n", "");
+            assert_eq!(expected, ini.to_string());
+            assert_eq!(Some("us-east-1"), removed.as_ref().and_then(|properties| properties.get("region")).map(|region| region.as_str()));
+            assert!(ini.section(Some("default")).is_none());
+        }
+
+        #[test]
+        fn remove_comments_of_sections_removed_one_after_another() {
+            let mut ini = Ini::load_from_str("[a]\nx = 1\n\n# b\n[b]\ny = 2\n\n# c\n[c]\nz = 3\n").unwrap();
+            ini.delete_section("b");
+            ini.delete_section("c");
+
+            assert_eq!("[a]\nx = 1\n", ini.to_string());
+        }
+
+        #[test]
+        fn remove_separator_of_last_section() {
+            let mut ini = Ini::load_from_str("[first]\na = 1\n\n[second]\nb = 2\n").unwrap();
+            ini.delete_section("second");
+
+            assert_eq!("[first]\na = 1\n", ini.to_string());
+        }
+
+        #[test]
+        fn return_none_if_section_not_found() {
+            let mut ini = Ini::load_from_str(super::CONFIG).unwrap();
+
+            assert!(ini.delete_section("not_existing").is_none());
+            assert_eq!(super::CONFIG, ini.to_string());
+        }
+    }
+
+    mod rename_section {
+        use aws_ini::Ini;
+
+        #[test]
+        fn rename_header_keeping_properties_and_lines() {
+            let mut ini = Ini::load_from_str(super::CONFIG).unwrap();
+
+            assert!(ini.rename_section("profile first", "profile renamed"));
+            assert_eq!(super::CONFIG.replace("[profile first]", "[profile renamed]"), ini.to_string());
+            assert_eq!(Some("arn_1"), ini.get_from(Some("profile renamed"), "role_arn"));
+            assert!(ini.section(Some("profile first")).is_none());
+        }
+
+        #[test]
+        fn return_false_if_section_not_found() {
+            let mut ini = Ini::new();
+
+            assert!(!ini.rename_section("first", "second"));
+        }
+    }
+}
@@ -1,19 +1,192 @@
 use clap::ArgMatches;
+use std::path::Path;
+
+// keys copied from selected assume role profile to default profile, role_arn and source_profile are always copied
+pub const DEFAULT_COPY_KEYS: [&str; 9] = [
+    "role_arn",
+    "source_profile",
+    "credential_source",
+    "mfa_serial",
+    "external_id",
+    "duration_seconds",
+    "role_session_name",
+    "region",
+    "output",
+];
 
 pub enum Config {
     Get(GetConfig),
     Set(SetConfig),
+    List(ListConfig),
+    Describe(DescribeConfig),
+    Validate(ValidateConfig),
+    Add(AddConfig),
+    AddRole(AddRoleConfig),
+    Remove(RemoveConfig),
+    Rename(RenameConfig),
+    Env(EnvConfig),
+    History(HistoryConfig),
+    RestoreBackup(RestoreBackupConfig),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum OutputFormat {
+    Table,
+    Plain,
+    Json,
+}
+
+// Auto uses custom picker command if it's set, otherwise fzf if it's installed and the built-in picker as the last resort
+#[derive(Debug, PartialEq, Clone)]
+pub enum PickerKind {
+    Auto,
+    Fzf,
+    Skim,
+    Peco,
+    Rofi,
+    Dmenu,
+    Builtin,
+    Custom,
+}
+
+impl PickerKind {
+    pub fn from_name(name: &str) -> Option<PickerKind> {
+        match name {
+            "auto" => Some(PickerKind::Auto),
+            "fzf" => Some(PickerKind::Fzf),
+            "sk" => Some(PickerKind::Skim),
+            "peco" => Some(PickerKind::Peco),
+            "rofi" => Some(PickerKind::Rofi),
+            "dmenu" => Some(PickerKind::Dmenu),
+            "builtin" => Some(PickerKind::Builtin),
+            "custom" => Some(PickerKind::Custom),
+            _ => None
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum DescribeFormat {
+    Text,
+    Json,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ErrorFormat {
+    Text,
+    Json,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Shell {
+    Posix,
+    Fish,
+    PowerShell,
 }
 
 pub struct GetConfig {
     pub credentials_path: String,
     pub config_path: String,
+    pub copy_keys: Vec<String>,
+    pub all: bool,
 }
 
 pub struct SetConfig {
+    pub credentials_path: String,
+    pub config_path: String,
+    pub history_path: String,
+    pub copied_keys_path: String,
+    pub copy_keys: Vec<String>,
+    pub pattern: String,
+    pub picker: PickerKind,
+}
+
+pub struct ListConfig {
+    pub credentials_path: String,
+    pub config_path: String,
+    pub copy_keys: Vec<String>,
+    pub output: OutputFormat,
+}
+
+pub struct DescribeConfig {
+    pub credentials_path: String,
+    pub config_path: String,
+    pub profile: String,
+    pub output: DescribeFormat,
+}
+
+pub struct ValidateConfig {
+    pub credentials_path: String,
+    pub config_path: String,
+}
+
+pub struct AddConfig {
+    pub credentials_path: String,
+    pub config_path: String,
+    pub profile: String,
+    pub region: Option<String>,
+    pub output: Option<String>,
+    pub force: bool,
+}
+
+// role arn is prompted for and source profile is selected with picker if they are not given
+pub struct AddRoleConfig {
+    pub credentials_path: String,
+    pub config_path: String,
+    pub profile: String,
+    pub role_arn: Option<String>,
+    pub source_profile: Option<String>,
+    pub mfa_serial: Option<String>,
+    pub external_id: Option<String>,
+    pub duration_seconds: Option<String>,
+    pub role_session_name: Option<String>,
+    pub region: Option<String>,
+    pub force: bool,
+    pub picker: PickerKind,
+}
+
+pub struct RemoveConfig {
+    pub credentials_path: String,
+    pub config_path: String,
+    pub history_path: String,
+    pub copied_keys_path: String,
+    pub copy_keys: Vec<String>,
+    pub profile: String,
+    pub cascade: bool,
+}
+
+pub struct RenameConfig {
+    pub credentials_path: String,
+    pub config_path: String,
+    pub history_path: String,
+    pub profile: String,
+    pub new_profile: String,
+}
+
+pub struct EnvConfig {
     pub credentials_path: String,
     pub config_path: String,
     pub pattern: String,
+    pub shell: Shell,
+    pub picker: PickerKind,
+}
+
+pub struct HistoryConfig {
+    pub credentials_path: String,
+    pub config_path: String,
+    pub history_path: String,
+    pub copied_keys_path: String,
+    pub copy_keys: Vec<String>,
+    pub select: bool,
+    pub picker: PickerKind,
+}
+
+pub struct RestoreBackupConfig {
+    pub credentials_path: String,
+    pub config_path: String,
+    pub history_path: String,
+    pub timestamp: Option<String>,
+    pub list: bool,
 }
 
 impl Config {
@@ -21,12 +194,104 @@ impl Config {
         match matches.subcommand() {
             ("get", Some(m)) => Some(Config::Get(GetConfig {
                                     credentials_path: get_credentials_path(m),
-                                    config_path: get_config_path(m)
+                                    config_path: get_config_path(m),
+                                    copy_keys: get_copy_keys(m),
+                                    all: m.is_present("all"),
                                 })),
             ("set", Some(m)) => Some(Config::Set(SetConfig {
+                                    credentials_path: get_credentials_path(m),
+                                    config_path: get_config_path(m),
+                                    history_path: get_history_path(m),
+                                    copied_keys_path: get_copied_keys_path(m),
+                                    copy_keys: get_copy_keys(m),
+                                    pattern: get_arg(m, "PROFILE_PATTERN", ""),
+                                    picker: get_picker(m),
+                                })),
+            ("back", Some(m)) => Some(Config::Set(SetConfig {
+                                    credentials_path: get_credentials_path(m),
+                                    config_path: get_config_path(m),
+                                    history_path: get_history_path(m),
+                                    copied_keys_path: get_copied_keys_path(m),
+                                    copy_keys: get_copy_keys(m),
+                                    pattern: "-".to_string(),
+                                    picker: get_picker(m),
+                                })),
+            ("list", Some(m)) => Some(Config::List(ListConfig {
+                                    credentials_path: get_credentials_path(m),
+                                    config_path: get_config_path(m),
+                                    copy_keys: get_copy_keys(m),
+                                    output: get_output_format(m),
+                                })),
+            ("describe", Some(m)) => Some(Config::Describe(DescribeConfig {
+                                    credentials_path: get_credentials_path(m),
+                                    config_path: get_config_path(m),
+                                    profile: get_arg(m, "PROFILE", ""),
+                                    output: get_describe_format(m),
+                                })),
+            ("validate", Some(m)) => Some(Config::Validate(ValidateConfig {
+                                    credentials_path: get_credentials_path(m),
+                                    config_path: get_config_path(m),
+                                })),
+            ("add", Some(m)) => Some(Config::Add(AddConfig {
+                                    credentials_path: get_credentials_path(m),
+                                    config_path: get_config_path(m),
+                                    profile: get_arg(m, "PROFILE", ""),
+                                    region: get_optional_arg(m, "region"),
+                                    output: get_optional_arg(m, "output"),
+                                    force: m.is_present("force"),
+                                })),
+            ("add-role", Some(m)) => Some(Config::AddRole(AddRoleConfig {
+                                    credentials_path: get_credentials_path(m),
+                                    config_path: get_config_path(m),
+                                    profile: get_arg(m, "PROFILE", ""),
+                                    role_arn: get_optional_arg(m, "role-arn"),
+                                    source_profile: get_optional_arg(m, "source-profile"),
+                                    mfa_serial: get_optional_arg(m, "mfa-serial"),
+                                    external_id: get_optional_arg(m, "external-id"),
+                                    duration_seconds: get_optional_arg(m, "duration-seconds"),
+                                    role_session_name: get_optional_arg(m, "role-session-name"),
+                                    region: get_optional_arg(m, "region"),
+                                    force: m.is_present("force"),
+                                    picker: get_picker(m),
+                                })),
+            ("remove", Some(m)) => Some(Config::Remove(RemoveConfig {
+                                    credentials_path: get_credentials_path(m),
+                                    config_path: get_config_path(m),
+                                    history_path: get_history_path(m),
+                                    copied_keys_path: get_copied_keys_path(m),
+                                    copy_keys: get_copy_keys(m),
+                                    profile: get_arg(m, "PROFILE", ""),
+                                    cascade: m.is_present("cascade"),
+                                })),
+            ("rename", Some(m)) => Some(Config::Rename(RenameConfig {
+                                    credentials_path: get_credentials_path(m),
+                                    config_path: get_config_path(m),
+                                    history_path: get_history_path(m),
+                                    profile: get_arg(m, "PROFILE", ""),
+                                    new_profile: get_arg(m, "NEW_PROFILE", ""),
+                                })),
+            ("env", Some(m)) => Some(Config::Env(EnvConfig {
                                     credentials_path: get_credentials_path(m),
                                     config_path: get_config_path(m),
                                     pattern: get_arg(m, "PROFILE_PATTERN", ""),
+                                    shell: get_shell(m),
+                                    picker: get_picker(m),
+                                })),
+            ("history", Some(m)) => Some(Config::History(HistoryConfig {
+                                    credentials_path: get_credentials_path(m),
+                                    config_path: get_config_path(m),
+                                    history_path: get_history_path(m),
+                                    copied_keys_path: get_copied_keys_path(m),
+                                    copy_keys: get_copy_keys(m),
+                                    select: m.is_present("select"),
+                                    picker: get_picker(m),
+                                })),
+            ("restore-backup", Some(m)) => Some(Config::RestoreBackup(RestoreBackupConfig {
+                                    credentials_path: get_credentials_path(m),
+                                    config_path: get_config_path(m),
+                                    history_path: get_history_path(m),
+                                    timestamp: get_optional_arg(m, "timestamp"),
+                                    list: m.is_present("list"),
                                 })),
             _ => None
         }
@@ -39,6 +304,10 @@ fn get_arg(matches: &ArgMatches, arg_name: &str, default_value: &str) -> String
            .to_string()
 }
 
+fn get_optional_arg(matches: &ArgMatches, arg_name: &str) -> Option<String> {
+    matches.value_of(arg_name).map(|value| value.to_string())
+}
+
 fn get_credentials_path(matches: &ArgMatches) -> String {
     get_arg(matches, "credentials-path", "~/.aws/credentials")
 }
@@ -46,3 +315,123 @@ fn get_credentials_path(matches: &ArgMatches) -> String {
 fn get_config_path(matches: &ArgMatches) -> String {
     get_arg(matches, "config-path", "~/.aws/config")
 }
+
+// raws keeps its own files next to AWS config file
+fn get_sibling_path(config_path: &str, file_name: &str) -> String {
+    Path::new(config_path).with_file_name(file_name)
+                          .to_string_lossy()
+                          .to_string()
+}
+
+fn get_history_path(matches: &ArgMatches) -> String {
+    get_sibling_path(&get_config_path(matches), "raws_history")
+}
+
+fn get_copied_keys_path(matches: &ArgMatches) -> String {
+    get_sibling_path(&get_config_path(matches), "raws_copied_keys")
+}
+
+pub fn get_raws_config_path(config_path: &str) -> String {
+    get_sibling_path(config_path, "raws_config")
+}
+
+fn get_copy_keys(matches: &ArgMatches) -> Vec<String> {
+    let mut keys = vec!("role_arn".to_string(), "source_profile".to_string());
+    let additional_keys: Vec<String> = match matches.value_of("copy-keys") {
+        Some(value) => value.split(',').map(|key| key.trim().to_string()).collect(),
+        None => DEFAULT_COPY_KEYS.iter().map(|key| key.to_string()).collect()
+    };
+    for key in additional_keys {
+        if !key.is_empty() && !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+    keys
+}
+
+fn get_output_format(matches: &ArgMatches) -> OutputFormat {
+    match get_arg(matches, "output", "table").as_str() {
+        "plain" => OutputFormat::Plain,
+        "json" => OutputFormat::Json,
+        _ => OutputFormat::Table
+    }
+}
+
+fn get_picker(matches: &ArgMatches) -> PickerKind {
+    PickerKind::from_name(&get_arg(matches, "picker", "auto")).unwrap_or(PickerKind::Auto)
+}
+
+pub fn get_error_format(matches: &ArgMatches) -> ErrorFormat {
+    match get_arg(matches, "error-format", "text").as_str() {
+        "json" => ErrorFormat::Json,
+        _ => ErrorFormat::Text
+    }
+}
+
+fn get_describe_format(matches: &ArgMatches) -> DescribeFormat {
+    match get_arg(matches, "output", "text").as_str() {
+        "json" => DescribeFormat::Json,
+        _ => DescribeFormat::Text
+    }
+}
+
+fn get_shell(matches: &ArgMatches) -> Shell {
+    match get_arg(matches, "shell", "bash").as_str() {
+        "fish" => Shell::Fish,
+        "powershell" => Shell::PowerShell,
+        _ => Shell::Posix
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod new {
+        use clap::App;
+        use config::Config;
+        use std::env;
+        use std::sync::Mutex;
+
+        // environment variables are shared by tests running in parallel
+        static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+        fn get_paths(args: &[&str], config_file: Option<&str>, credentials_file: Option<&str>) -> (String, String) {
+            let _lock = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            for (variable, value) in [("AWS_CONFIG_FILE", config_file), ("AWS_SHARED_CREDENTIALS_FILE", credentials_file)] {
+                match value {
+                    Some(value) => env::set_var(variable, value),
+                    None => env::remove_var(variable)
+                }
+            }
+
+            let yaml = clap::load_yaml!("cli.yaml");
+            let matches = App::from_yaml(yaml).get_matches_from_safe(args).unwrap();
+            match Config::new(&matches) {
+                Some(Config::Get(config)) => (config.config_path, config.credentials_path),
+                _ => panic!("expecting get config")
+            }
+        }
+
+        #[test]
+        fn use_flags_over_environment_variables() {
+            let paths = get_paths(&["raws", "get", "--config-path", "flag_config", "--credentials-path", "flag_credentials"],
+                                  Some("env_config"),
+                                  Some("env_credentials"));
+
+            assert_eq!(("flag_config".to_string(), "flag_credentials".to_string()), paths);
+        }
+
+        #[test]
+        fn use_environment_variables_over_default_paths() {
+            let paths = get_paths(&["raws", "get"], Some("env_config"), Some("env_credentials"));
+
+            assert_eq!(("env_config".to_string(), "env_credentials".to_string()), paths);
+        }
+
+        #[test]
+        fn use_default_paths_if_neither_flags_nor_environment_variables_are_set() {
+            let paths = get_paths(&["raws", "get"], None, None);
+
+            assert_eq!(("~/.aws/config".to_string(), "~/.aws/credentials".to_string()), paths);
+        }
+    }
+}
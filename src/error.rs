@@ -0,0 +1,321 @@
+use serde_json::Value;
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::io::ErrorKind;
+
+// exit codes returned by raws, documented in README so wrapper scripts can branch on the outcome
+pub const EXIT_OTHER: i32 = 1;
+pub const EXIT_FILE: i32 = 3;
+pub const EXIT_PARSE: i32 = 4;
+pub const EXIT_PROFILE_NOT_FOUND: i32 = 5;
+pub const EXIT_AMBIGUOUS_PROFILE: i32 = 6;
+pub const EXIT_PICKER_UNAVAILABLE: i32 = 7;
+pub const EXIT_WRITE_FAILED: i32 = 8;
+pub const EXIT_INVALID_PROFILE: i32 = 9;
+pub const EXIT_INVALID_INPUT: i32 = 10;
+pub const EXIT_PICKER_FAILED: i32 = 11;
+pub const EXIT_PICKER_CANCELLED: i32 = 130;
+
+#[derive(Debug, PartialEq)]
+pub enum RawsError {
+    FileNotFound { path: String },
+    PermissionDenied { path: String },
+    ReadFailed { path: String, reason: String },
+    ParseError { path: String, line: usize, column: usize, text: String, message: String, hint: String },
+    ProfileNotFound { profile: String },
+    AmbiguousProfile { profiles: Vec<String> },
+    PickerCancelled,
+    // Ctrl-C while a value is prompted for
+    InputCancelled,
+    PickerUnavailable { picker: String, reason: String },
+    PickerFailed { picker: String, reason: String },
+    WriteFailed { path: String, reason: String },
+    SourceProfileNotFound { profile: String, source_profile: String },
+    // profiles of the cycle starting and ending with the same profile, e.g. a -> b -> a
+    SourceProfileCycle { profiles: Vec<String> },
+    InvalidProfiles { errors: Vec<RawsError> },
+    ProfileExists { profile: String },
+    // profiles that would be left with a dangling source_profile if the profile is removed
+    ProfileReferenced { profile: String, profiles: Vec<String> },
+    // name of the invalid argument or prompted value, the value itself is left out since it may be a secret
+    InvalidValue { name: String, reason: String },
+    Other(String),
+}
+
+impl RawsError {
+    pub fn read_failed(path: &str, error: &io::Error) -> RawsError {
+        let path = path.to_string();
+        match error.kind() {
+            ErrorKind::NotFound => RawsError::FileNotFound { path },
+            ErrorKind::PermissionDenied => RawsError::PermissionDenied { path },
+            _ => RawsError::ReadFailed { path, reason: error.to_string() }
+        }
+    }
+
+    pub fn write_failed(path: impl fmt::Display, reason: impl fmt::Display) -> RawsError {
+        RawsError::WriteFailed { path: path.to_string(), reason: reason.to_string() }
+    }
+
+    pub fn invalid_value(name: &str, reason: &str) -> RawsError {
+        RawsError::InvalidValue { name: name.to_string(), reason: reason.to_string() }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            RawsError::FileNotFound { .. } => "file_not_found",
+            RawsError::PermissionDenied { .. } => "permission_denied",
+            RawsError::ReadFailed { .. } => "read_failed",
+            RawsError::ParseError { .. } => "parse_error",
+            RawsError::ProfileNotFound { .. } => "profile_not_found",
+            RawsError::AmbiguousProfile { .. } => "ambiguous_profile",
+            RawsError::PickerCancelled => "picker_cancelled",
+            RawsError::InputCancelled => "input_cancelled",
+            RawsError::PickerUnavailable { .. } => "picker_unavailable",
+            RawsError::PickerFailed { .. } => "picker_failed",
+            RawsError::WriteFailed { .. } => "write_failed",
+            RawsError::SourceProfileNotFound { .. } => "source_profile_not_found",
+            RawsError::SourceProfileCycle { .. } => "source_profile_cycle",
+            RawsError::InvalidProfiles { .. } => "invalid_profiles",
+            RawsError::ProfileExists { .. } => "profile_exists",
+            RawsError::ProfileReferenced { .. } => "profile_referenced",
+            RawsError::InvalidValue { .. } => "invalid_value",
+            RawsError::Other(_) => "other",
+        }
+    }
+
+    // details are only included for errors that wrapper scripts may want to act on
+    fn details(&self) -> Value {
+        match self {
+            RawsError::FileNotFound { path } |
+            RawsError::PermissionDenied { path } |
+            RawsError::ReadFailed { path, .. } |
+            RawsError::WriteFailed { path, .. } => json!({ "path": path }),
+            RawsError::ParseError { path, line, column, text, hint, .. } =>
+                json!({ "path": path, "line": line, "column": column, "text": text, "hint": hint }),
+            RawsError::ProfileNotFound { profile } |
+            RawsError::ProfileExists { profile } => json!({ "profile": profile }),
+            RawsError::AmbiguousProfile { profiles } => json!({ "profiles": profiles }),
+            RawsError::PickerUnavailable { picker, .. } |
+            RawsError::PickerFailed { picker, .. } => json!({ "picker": picker }),
+            RawsError::SourceProfileNotFound { profile, source_profile } =>
+                json!({ "profile": profile, "source_profile": source_profile }),
+            RawsError::SourceProfileCycle { profiles } => json!({ "profiles": profiles }),
+            RawsError::InvalidProfiles { errors } =>
+                json!({ "errors": errors.iter().map(|error| to_json(error)["error"].clone()).collect::<Vec<Value>>() }),
+            RawsError::ProfileReferenced { profile, profiles } => json!({ "profile": profile, "profiles": profiles }),
+            RawsError::InvalidValue { name, .. } => json!({ "name": name }),
+            RawsError::PickerCancelled |
+            RawsError::InputCancelled |
+            RawsError::Other(_) => json!({}),
+        }
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            RawsError::FileNotFound { .. } |
+            RawsError::PermissionDenied { .. } |
+            RawsError::ReadFailed { .. } => EXIT_FILE,
+            RawsError::ParseError { .. } => EXIT_PARSE,
+            RawsError::ProfileNotFound { .. } => EXIT_PROFILE_NOT_FOUND,
+            RawsError::AmbiguousProfile { .. } => EXIT_AMBIGUOUS_PROFILE,
+            RawsError::PickerCancelled |
+            RawsError::InputCancelled => EXIT_PICKER_CANCELLED,
+            RawsError::PickerUnavailable { .. } => EXIT_PICKER_UNAVAILABLE,
+            RawsError::PickerFailed { .. } => EXIT_PICKER_FAILED,
+            RawsError::WriteFailed { .. } => EXIT_WRITE_FAILED,
+            RawsError::SourceProfileNotFound { .. } |
+            RawsError::SourceProfileCycle { .. } |
+            RawsError::InvalidProfiles { .. } => EXIT_INVALID_PROFILE,
+            RawsError::ProfileExists { .. } |
+            RawsError::ProfileReferenced { .. } |
+            RawsError::InvalidValue { .. } => EXIT_INVALID_INPUT,
+            RawsError::Other(_) => EXIT_OTHER,
+        }
+    }
+}
+
+impl fmt::Display for RawsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RawsError::FileNotFound { path } => write!(f, "file {} not found", path),
+            RawsError::PermissionDenied { path } => write!(f, "permission denied reading file {}", path),
+            RawsError::ReadFailed { path, reason } => write!(f, "failed to load file {}: {}", path, reason),
+            RawsError::ParseError { path, line, column, text, message, hint } => {
+                let line_number = line.to_string();
+                writeln!(f, "invalid file {}: line {}, column {}: {}", path, line, column, message)?;
+                writeln!(f, "    {} | {}", line_number, text)?;
+                writeln!(f, "    {} | {}^", " ".repeat(line_number.len()), " ".repeat(column.saturating_sub(1)))?;
+                write!(f, "hint: {}", hint)
+            },
+            RawsError::ProfileNotFound { profile } => write!(f, "profile [{}] not found in both config and credentials file", profile),
+            RawsError::AmbiguousProfile { profiles } =>
+                write!(f, "default profile matches multiple profiles: {}, use --all to list all of them", profiles.join(", ")),
+            RawsError::PickerCancelled => write!(f, "profile selection cancelled"),
+            RawsError::InputCancelled => write!(f, "input cancelled"),
+            RawsError::PickerUnavailable { picker, reason } => write!(f, "failed to start {}: {}", picker, reason),
+            RawsError::PickerFailed { picker, reason } => write!(f, "{} failed: {}", picker, reason),
+            RawsError::WriteFailed { path, reason } => write!(f, "failed to write file {}: {}", path, reason),
+            RawsError::SourceProfileNotFound { profile, source_profile } =>
+                write!(f, "source profile [{}] of profile [{}] not found in both config and credentials file", source_profile, profile),
+            RawsError::SourceProfileCycle { profiles } => write!(f, "source profiles form a cycle: {}", profiles.join(" -> ")),
+            RawsError::InvalidProfiles { errors } => {
+                write!(f, "found {} invalid profile(s)", errors.len())?;
+                errors.iter().try_for_each(|error| write!(f, "\n  - {}", error))
+            },
+            RawsError::ProfileExists { profile } => write!(f, "profile [{}] already exists, use --force to replace it", profile),
+            RawsError::ProfileReferenced { profile, profiles } => {
+                let profiles: Vec<String> = profiles.iter().map(|profile| format!("[{}]", profile)).collect();
+                write!(f, "profile [{}] is source profile of {}, use --cascade to remove them too", profile, profiles.join(", "))
+            },
+            RawsError::InvalidValue { name, reason } => write!(f, "invalid {}: {}", name, reason),
+            RawsError::Other(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl Error for RawsError {}
+
+impl From<String> for RawsError {
+    fn from(message: String) -> Self {
+        RawsError::Other(message)
+    }
+}
+
+// exit code of any error returned by a handler, errors not raised by raws itself are reported as EXIT_OTHER
+pub fn exit_code_of(error: &(dyn Error + 'static)) -> i32 {
+    error.downcast_ref::<RawsError>()
+         .map(RawsError::exit_code)
+         .unwrap_or(EXIT_OTHER)
+}
+
+// cancelling the picker or a prompt is a choice of the user rather than a failure, so only its exit code reports it
+pub fn is_cancelled(error: &(dyn Error + 'static)) -> bool {
+    matches!(error.downcast_ref::<RawsError>(), Some(RawsError::PickerCancelled) | Some(RawsError::InputCancelled))
+}
+
+// machine readable form of any error returned by a handler, used with `--error-format json`,
+// message is the first line of the human readable error since details are given as separate fields
+pub fn to_json(error: &(dyn Error + 'static)) -> Value {
+    let (kind, details) = match error.downcast_ref::<RawsError>() {
+        Some(raws_error) => (raws_error.kind(), raws_error.details()),
+        None => ("other", json!({}))
+    };
+    let message = error.to_string().lines().next().unwrap_or_default().to_string();
+
+    let mut value = json!({
+        "kind": kind,
+        "message": message,
+        "exit_code": exit_code_of(error),
+    });
+    if let (Some(value), Value::Object(details)) = (value.as_object_mut(), details) {
+        value.extend(details);
+    }
+    json!({ "error": value })
+}
+
+#[cfg(test)]
+mod tests {
+    mod read_failed {
+        use error::RawsError;
+        use std::io::{ Error, ErrorKind };
+
+        #[test]
+        fn distinguish_permission_denied_from_other_errors() {
+            let permission_denied = RawsError::read_failed("/some/config", &Error::from(ErrorKind::PermissionDenied));
+            let other = RawsError::read_failed("/some/config", &Error::from(ErrorKind::InvalidData));
+
+            assert_eq!("permission denied reading file /some/config", permission_denied.to_string());
+            assert!(other.to_string().starts_with("failed to load file /some/config"));
+        }
+    }
+
+    mod exit_code_of {
+        use error::{ self, RawsError };
+        use std::error::Error;
+
+        #[test]
+        fn return_exit_code_of_raws_error() {
+            let error: Box<dyn Error> = RawsError::ProfileNotFound { profile: "some_profile".to_string() }.into();
+
+            assert_eq!(error::EXIT_PROFILE_NOT_FOUND, error::exit_code_of(error.as_ref()));
+        }
+
+        #[test]
+        fn return_distinct_exit_codes_for_picker_cancelled_unavailable_and_failed() {
+            let cancelled: Box<dyn Error> = RawsError::PickerCancelled.into();
+            let unavailable: Box<dyn Error> = RawsError::PickerUnavailable { picker: "fzf".to_string(), reason: "not found".to_string() }.into();
+            let failed: Box<dyn Error> = RawsError::PickerFailed { picker: "fzf".to_string(), reason: "exited with 2".to_string() }.into();
+
+            assert_eq!(error::EXIT_PICKER_CANCELLED, error::exit_code_of(cancelled.as_ref()));
+            assert_eq!(error::EXIT_PICKER_UNAVAILABLE, error::exit_code_of(unavailable.as_ref()));
+            assert_eq!(error::EXIT_PICKER_FAILED, error::exit_code_of(failed.as_ref()));
+        }
+
+        #[test]
+        fn return_exit_code_of_cancelled_picker_for_cancelled_prompt() {
+            let error: Box<dyn Error> = RawsError::InputCancelled.into();
+
+            assert_eq!(error::EXIT_PICKER_CANCELLED, error::exit_code_of(error.as_ref()));
+            assert!(error::is_cancelled(error.as_ref()));
+        }
+
+        #[test]
+        fn return_exit_other_for_other_errors() {
+            let error: Box<dyn Error> = String::from("some error").into();
+
+            assert_eq!(error::EXIT_OTHER, error::exit_code_of(error.as_ref()));
+        }
+    }
+
+    mod to_json {
+        use error::{ self, RawsError };
+        use std::error::Error;
+
+        #[test]
+        fn include_position_of_parse_error() {
+            let error: Box<dyn Error> = RawsError::ParseError {
+                path: "/some/config".to_string(),
+                line: 3,
+                column: 1,
+                text: "region us-east-1".to_string(),
+                message: "expecting \"key = value\" or \"[section]\"".to_string(),
+                hint: "some hint".to_string(),
+            }.into();
+
+            let result = error::to_json(error.as_ref());
+
+            assert_eq!("parse_error", result["error"]["kind"]);
+            assert_eq!("invalid file /some/config: line 3, column 1: expecting \"key = value\" or \"[section]\"", result["error"]["message"]);
+            assert_eq!(error::EXIT_PARSE, result["error"]["exit_code"]);
+            assert_eq!(3, result["error"]["line"]);
+            assert_eq!(1, result["error"]["column"]);
+            assert_eq!("region us-east-1", result["error"]["text"]);
+            assert_eq!("some hint", result["error"]["hint"]);
+        }
+
+        #[test]
+        fn include_each_error_of_invalid_profiles() {
+            let error: Box<dyn Error> = RawsError::InvalidProfiles { errors: vec!(
+                RawsError::SourceProfileNotFound { profile: "assumed".to_string(), source_profile: "removed".to_string() },
+            ) }.into();
+
+            let result = error::to_json(error.as_ref());
+
+            assert_eq!("found 1 invalid profile(s)", result["error"]["message"]);
+            assert_eq!("source_profile_not_found", result["error"]["errors"][0]["kind"]);
+            assert_eq!("assumed", result["error"]["errors"][0]["profile"]);
+            assert_eq!("removed", result["error"]["errors"][0]["source_profile"]);
+        }
+
+        #[test]
+        fn return_other_kind_for_other_errors() {
+            let error: Box<dyn Error> = String::from("some error").into();
+
+            let result = error::to_json(error.as_ref());
+
+            assert_eq!("other", result["error"]["kind"]);
+            assert_eq!("some error", result["error"]["message"]);
+        }
+    }
+}
@@ -0,0 +1,365 @@
+use error::RawsError;
+use shellexpand::tilde;
+use std::error::Error;
+use std::fs;
+use std::io::{ self, Write };
+use std::path::{ Path, PathBuf };
+use std::time::{ SystemTime, UNIX_EPOCH };
+
+const TEMP_EXTENSION: &str = "raws-tmp";
+const BACKUP_EXTENSION: &str = "bak";
+const ROLLBACK_EXTENSION: &str = "raws-rollback";
+const MAX_BACKUPS_PER_FILE: usize = 5;
+#[cfg(unix)]
+const NEW_FILE_MODE: u32 = 0o600;
+
+// backups are kept for restore-backup, rollback copies only until all files are written
+#[derive(Clone, Copy)]
+enum BackupKind {
+    Kept(u64),
+    Rollback,
+}
+
+struct PendingWrite {
+    path: PathBuf,
+    temp_path: PathBuf,
+    backup_path: Option<PathBuf>,
+}
+
+fn expand(path: &str) -> PathBuf {
+    PathBuf::from(tilde(path).to_string())
+}
+
+fn append_extension(path: &Path, extension: &str) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(format!(".{}", extension));
+    path.with_file_name(file_name)
+}
+
+fn get_backup_path(path: &Path, timestamp: u64) -> PathBuf {
+    append_extension(path, &format!("{}.{}", timestamp, BACKUP_EXTENSION))
+}
+
+fn current_timestamp() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH)
+                     .map(|duration| duration.as_millis() as u64)
+                     .unwrap_or(0)
+}
+
+// files written together share the timestamp of their backups, it's moved forward until none of the files
+// has a backup with it yet so that writes within the same millisecond never overwrite an earlier backup
+fn find_unused_timestamp(files: &[(String, String)]) -> u64 {
+    let mut timestamp = current_timestamp();
+    while files.iter().any(|(_, path)| get_backup_path(&expand(path), timestamp).exists()) {
+        timestamp += 1;
+    }
+    timestamp
+}
+
+fn create_parent_directory(path: &Path) -> Result<(), RawsError> {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() && !parent.exists() => fs::create_dir_all(parent)
+            .map_err(|e| RawsError::write_failed(path.display(), format!("failed to create directory {}: {}", parent.display(), e))),
+        _ => Ok(())
+    }
+}
+
+fn write_temp_file(path: &Path, content: &str) -> Result<PathBuf, RawsError> {
+    create_parent_directory(path)?;
+    let temp_path = append_extension(path, TEMP_EXTENSION);
+    let to_write_error = |e: io::Error| RawsError::write_failed(path.display(), format!("failed to write temp file {}: {}", temp_path.display(), e));
+
+    // keep permissions of the original file, e.g. 0600 of credentials file, the temp file is created with them
+    // so that its content is never readable by others, not even before its permissions are set
+    let permissions = match fs::metadata(path) {
+        Ok(metadata) => Some(metadata.permissions()),
+        Err(_) => new_file_permissions()
+    };
+    // a temp file left over by an interrupted write may have other permissions
+    let _ = fs::remove_file(&temp_path);
+    let mut temp_file = create_temp_file(&temp_path, &permissions).map_err(to_write_error)?;
+    temp_file.write_all(content.as_bytes()).map_err(to_write_error)?;
+
+    // the mode given on creation is narrowed by umask
+    if let Some(permissions) = permissions {
+        fs::set_permissions(&temp_path, permissions)
+            .map_err(|e| RawsError::write_failed(path.display(), format!("failed to set permissions of {}: {}", temp_path.display(), e)))?;
+    }
+
+    Ok(temp_path)
+}
+
+#[cfg(unix)]
+fn create_temp_file(temp_path: &Path, permissions: &Option<fs::Permissions>) -> io::Result<fs::File> {
+    use std::os::unix::fs::{ OpenOptionsExt, PermissionsExt };
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    if let Some(permissions) = permissions {
+        options.mode(permissions.mode());
+    }
+    options.open(temp_path)
+}
+
+#[cfg(not(unix))]
+fn create_temp_file(temp_path: &Path, _: &Option<fs::Permissions>) -> io::Result<fs::File> {
+    fs::OpenOptions::new().write(true).create_new(true).open(temp_path)
+}
+
+// files created by raws may contain credentials so they are only readable by the owner
+#[cfg(unix)]
+fn new_file_permissions() -> Option<fs::Permissions> {
+    use std::os::unix::fs::PermissionsExt;
+    Some(fs::Permissions::from_mode(NEW_FILE_MODE))
+}
+
+#[cfg(not(unix))]
+fn new_file_permissions() -> Option<fs::Permissions> {
+    None
+}
+
+fn backup_file(path: &Path, kind: BackupKind) -> Result<Option<PathBuf>, RawsError> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let backup_path = match kind {
+        BackupKind::Kept(timestamp) => get_backup_path(path, timestamp),
+        BackupKind::Rollback => append_extension(path, ROLLBACK_EXTENSION)
+    };
+    fs::copy(path, &backup_path)
+        .map(|_| Some(backup_path.clone()))
+        .map_err(|e| RawsError::write_failed(path.display(), format!("failed to back up to {}: {}", backup_path.display(), e)))
+}
+
+fn rollback(committed: &[PendingWrite], pending: &[PendingWrite]) {
+    for write in committed {
+        match write.backup_path {
+            Some(ref backup_path) => { let _ = fs::copy(backup_path, &write.path); },
+            None => { let _ = fs::remove_file(&write.path); }
+        }
+    }
+    for write in pending {
+        let _ = fs::remove_file(&write.temp_path);
+    }
+}
+
+fn prepare(files: &[(String, String)], kind: BackupKind) -> Result<Vec<PendingWrite>, RawsError> {
+    let mut pending: Vec<PendingWrite> = vec!();
+    for (content, path) in files {
+        let path = expand(path);
+        let result = write_temp_file(&path, content)
+            .and_then(|temp_path| backup_file(&path, kind).map(|backup_path| (temp_path, backup_path)));
+
+        match result {
+            Ok((temp_path, backup_path)) => pending.push(PendingWrite { path, temp_path, backup_path }),
+            Err(e) => {
+                let _ = fs::remove_file(append_extension(&path, TEMP_EXTENSION));
+                rollback(&[], &pending);
+                return Err(e);
+            }
+        }
+    }
+    Ok(pending)
+}
+
+fn commit(pending: Vec<PendingWrite>) -> Result<(), RawsError> {
+    for index in 0..pending.len() {
+        let write = &pending[index];
+        if let Err(e) = fs::rename(&write.temp_path, &write.path) {
+            rollback(&pending[..index], &pending[index..]);
+            return Err(RawsError::write_failed(write.path.display(), e));
+        }
+    }
+    Ok(())
+}
+
+pub fn find_backup_timestamps(path: &str) -> Vec<u64> {
+    let path = expand(path);
+    let prefix = format!("{}.", path.file_name().unwrap_or_default().to_string_lossy());
+    let suffix = format!(".{}", BACKUP_EXTENSION);
+    let directory = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from(".")
+    };
+
+    let mut timestamps: Vec<u64> = fs::read_dir(directory)
+        .map(|entries| entries.filter_map(|entry| entry.ok())
+                              .filter_map(|entry| {
+                                  let file_name = entry.file_name().to_string_lossy().to_string();
+                                  if file_name.starts_with(&prefix) && file_name.ends_with(&suffix) {
+                                      file_name[prefix.len()..file_name.len() - suffix.len()].parse().ok()
+                                  } else {
+                                      None
+                                  }
+                              })
+                              .collect())
+        .unwrap_or_default();
+    timestamps.sort_unstable_by(|a, b| b.cmp(a));
+    timestamps
+}
+
+pub fn read_backup(path: &str, timestamp: u64) -> Result<String, Box<dyn Error>> {
+    let backup_path = get_backup_path(&expand(path), timestamp);
+    fs::read_to_string(&backup_path)
+        .map_err(|e| RawsError::read_failed(&backup_path.display().to_string(), &e).into())
+}
+
+fn prune_backups(path: &str) {
+    for timestamp in find_backup_timestamps(path).into_iter().skip(MAX_BACKUPS_PER_FILE) {
+        let _ = fs::remove_file(get_backup_path(&expand(path), timestamp));
+    }
+}
+
+// writes all files or none of them: each file is written to a temp file first and only renamed over
+// the original once all temp files and backups are in place, already renamed files are restored
+// from their backups if a later rename fails
+pub fn write_files(files: Vec<(String, String)>) -> Result<(), Box<dyn Error>> {
+    let pending = prepare(&files, BackupKind::Kept(find_unused_timestamp(&files)))?;
+    commit(pending)?;
+
+    for (_, path) in files {
+        prune_backups(&path);
+    }
+    Ok(())
+}
+
+// same as write_files but without taking a new backup, restoring a backup would otherwise create a newer one
+// that the next restore picks instead of the older backups
+pub fn restore_files(files: Vec<(String, String)>) -> Result<(), Box<dyn Error>> {
+    let result = prepare(&files, BackupKind::Rollback).and_then(commit);
+
+    for (_, path) in &files {
+        let _ = fs::remove_file(append_extension(&expand(path), ROLLBACK_EXTENSION));
+    }
+    result.map_err(|e| e.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env::temp_dir;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::process;
+
+    fn get_test_directory(test_name: &str) -> PathBuf {
+        let directory = temp_dir().join(format!("raws_{}_{}", process::id(), test_name));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    mod write_files {
+        use file_transaction;
+        use std::fs;
+
+        #[test]
+        fn write_all_files_and_keep_backups_of_previous_content() {
+            let directory = super::get_test_directory("write_all_files_and_keep_backups_of_previous_content");
+            let config_path = directory.join("config").display().to_string();
+            let credentials_path = directory.join("credentials").display().to_string();
+            fs::write(&config_path, "old config").unwrap();
+
+            let result = file_transaction::write_files(vec!(
+                ("new config".to_string(), config_path.clone()),
+                ("new credentials".to_string(), credentials_path.clone()),
+            ));
+
+            assert!(result.is_ok());
+            assert_eq!("new config", fs::read_to_string(&config_path).unwrap());
+            assert_eq!("new credentials", fs::read_to_string(&credentials_path).unwrap());
+            let timestamps = file_transaction::find_backup_timestamps(&config_path);
+            assert_eq!(1, timestamps.len());
+            assert_eq!("old config", file_transaction::read_backup(&config_path, timestamps[0]).unwrap());
+            assert!(file_transaction::find_backup_timestamps(&credentials_path).is_empty());
+        }
+
+        #[test]
+        fn keep_backup_of_every_write_if_files_are_written_back_to_back() {
+            let directory = super::get_test_directory("keep_backup_of_every_write_if_files_are_written_back_to_back");
+            let config_path = directory.join("config").display().to_string();
+            fs::write(&config_path, "first config").unwrap();
+
+            file_transaction::write_files(vec!(("second config".to_string(), config_path.clone()))).unwrap();
+            file_transaction::write_files(vec!(("third config".to_string(), config_path.clone()))).unwrap();
+
+            let timestamps = file_transaction::find_backup_timestamps(&config_path);
+            assert_eq!(2, timestamps.len());
+            assert_eq!("second config", file_transaction::read_backup(&config_path, timestamps[0]).unwrap());
+            assert_eq!("first config", file_transaction::read_backup(&config_path, timestamps[1]).unwrap());
+        }
+
+        #[test]
+        fn restore_files_without_taking_a_new_backup() {
+            let directory = super::get_test_directory("restore_files_without_taking_a_new_backup");
+            let config_path = directory.join("config").display().to_string();
+            fs::write(&config_path, "current config").unwrap();
+
+            let result = file_transaction::restore_files(vec!(("restored config".to_string(), config_path.clone())));
+
+            assert!(result.is_ok());
+            assert_eq!("restored config", fs::read_to_string(&config_path).unwrap());
+            assert!(file_transaction::find_backup_timestamps(&config_path).is_empty());
+            assert!(!directory.join("config.raws-rollback").exists());
+        }
+
+        #[cfg(unix)]
+        #[test]
+        fn create_missing_file_readable_by_owner_only() {
+            use std::os::unix::fs::PermissionsExt;
+            let directory = super::get_test_directory("create_missing_file_readable_by_owner_only");
+            let credentials_path = directory.join("credentials").display().to_string();
+
+            let result = file_transaction::write_files(vec!(("new credentials".to_string(), credentials_path.clone())));
+
+            assert!(result.is_ok());
+            assert_eq!(0o600, fs::metadata(&credentials_path).unwrap().permissions().mode() & 0o777);
+        }
+
+        #[cfg(unix)]
+        #[test]
+        fn replace_stale_temp_file_keeping_permissions_of_existing_file() {
+            use std::os::unix::fs::PermissionsExt;
+            let directory = super::get_test_directory("replace_stale_temp_file_keeping_permissions_of_existing_file");
+            let credentials_path = directory.join("credentials").display().to_string();
+            fs::write(&credentials_path, "old credentials").unwrap();
+            fs::set_permissions(&credentials_path, fs::Permissions::from_mode(0o600)).unwrap();
+            fs::write(directory.join("credentials.raws-tmp"), "stale temp file").unwrap();
+            fs::set_permissions(directory.join("credentials.raws-tmp"), fs::Permissions::from_mode(0o644)).unwrap();
+
+            let result = file_transaction::write_files(vec!(("new credentials".to_string(), credentials_path.clone())));
+
+            assert!(result.is_ok());
+            assert_eq!("new credentials", fs::read_to_string(&credentials_path).unwrap());
+            assert_eq!(0o600, fs::metadata(&credentials_path).unwrap().permissions().mode() & 0o777);
+        }
+
+        #[test]
+        fn create_missing_parent_directory() {
+            let directory = super::get_test_directory("create_missing_parent_directory");
+            let credentials_path = directory.join(".aws").join("credentials").display().to_string();
+
+            let result = file_transaction::write_files(vec!(("new credentials".to_string(), credentials_path.clone())));
+
+            assert!(result.is_ok());
+            assert_eq!("new credentials", fs::read_to_string(&credentials_path).unwrap());
+        }
+
+        #[test]
+        fn leave_all_files_unchanged_if_any_file_fails_to_be_written() {
+            let directory = super::get_test_directory("leave_all_files_unchanged_if_any_file_fails_to_be_written");
+            let config_path = directory.join("config").display().to_string();
+            let credentials_path = directory.join("not_a_directory").join("credentials").display().to_string();
+            fs::write(&config_path, "old config").unwrap();
+            fs::write(directory.join("not_a_directory"), "").unwrap();
+
+            let result = file_transaction::write_files(vec!(
+                ("new config".to_string(), config_path.clone()),
+                ("new credentials".to_string(), credentials_path),
+            ));
+
+            assert!(result.is_err());
+            assert_eq!("old config", fs::read_to_string(&config_path).unwrap());
+            assert!(!directory.join("config.raws-tmp").exists());
+        }
+    }
+}
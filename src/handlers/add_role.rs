@@ -0,0 +1,240 @@
+use handlers::common::{ get_config_section_name, load_ini, to_profile_name };
+use handlers::add::{ validate_profile_name, validate_region };
+use handlers::set::{ get_all_profile_names, ASSUME_ROLE_KEYS };
+use handlers::source_profile::{ profile_exists, resolve_source_profiles };
+use handlers::picker::PickerResult;
+use aws_ini::Ini;
+use config::AddRoleConfig;
+use error::RawsError;
+use std::error::Error;
+
+// limits of AssumeRole parameters
+const MIN_DURATION_SECONDS: u32 = 900;
+const MAX_DURATION_SECONDS: u32 = 43200;
+const ROLE_SESSION_NAME_LENGTH: (usize, usize) = (2, 64);
+const EXTERNAL_ID_LENGTH: (usize, usize) = (2, 1224);
+
+// resource of an iam arn, e.g. role/some-role in arn:aws:iam::123456789012:role/some-role,
+// partition is aws-cn or aws-us-gov outside of standard regions
+fn get_iam_resource(arn: &str) -> Option<&str> {
+    let parts: Vec<&str> = arn.splitn(6, ':').collect();
+    match parts.as_slice() {
+        ["arn", partition, "iam", "", account_id, resource]
+            if partition.starts_with("aws") && account_id.len() == 12 && account_id.chars().all(|c| c.is_ascii_digit())
+          => Some(resource),
+        _ => None
+    }
+}
+
+fn has_named_resource(arn: &str, resource_type: &str) -> bool {
+    get_iam_resource(arn)
+        .filter(|resource| resource.starts_with(resource_type))
+        .map(|resource| resource.len() > resource_type.len() && !resource.ends_with('/'))
+        .unwrap_or(false)
+}
+
+fn validate_role_arn(role_arn: &str) -> Result<(), RawsError> {
+    if !has_named_resource(role_arn, "role/") {
+        return Err(RawsError::invalid_value("role arn", "must look like arn:aws:iam::123456789012:role/some-role"));
+    }
+    Ok(())
+}
+
+// serial number of a hardware device or arn of a virtual one
+fn validate_mfa_serial(mfa_serial: &str) -> Result<(), RawsError> {
+    let is_valid = if mfa_serial.starts_with("arn:") {
+        has_named_resource(mfa_serial, "mfa/")
+    } else {
+        !mfa_serial.is_empty() && mfa_serial.chars().all(|c| c.is_ascii_alphanumeric())
+    };
+    if !is_valid {
+        return Err(RawsError::invalid_value("mfa serial", "must look like arn:aws:iam::123456789012:mfa/some-user or be a serial number"));
+    }
+    Ok(())
+}
+
+fn validate_duration_seconds(duration_seconds: &str) -> Result<(), RawsError> {
+    match duration_seconds.parse::<u32>() {
+        Ok(seconds) if (MIN_DURATION_SECONDS..=MAX_DURATION_SECONDS).contains(&seconds) => Ok(()),
+        _ => Err(RawsError::invalid_value("duration seconds", &format!("must be a number from {} to {}", MIN_DURATION_SECONDS, MAX_DURATION_SECONDS)))
+    }
+}
+
+fn validate_identifier(name: &str, value: &str, (min_length, max_length): (usize, usize), special_characters: &str) -> Result<(), RawsError> {
+    if value.len() < min_length || value.len() > max_length {
+        return Err(RawsError::invalid_value(name, &format!("must be {} to {} characters long", min_length, max_length)));
+    }
+    if !value.chars().all(|c| c.is_ascii_alphanumeric() || special_characters.contains(c)) {
+        return Err(RawsError::invalid_value(name, &format!("must only contain letters, digits and {}", special_characters)));
+    }
+    Ok(())
+}
+
+fn validate_settings(config: &AddRoleConfig) -> Result<(), RawsError> {
+    if let Some(ref role_arn) = config.role_arn {
+        validate_role_arn(role_arn)?;
+    }
+    if let Some(ref mfa_serial) = config.mfa_serial {
+        validate_mfa_serial(mfa_serial)?;
+    }
+    if let Some(ref external_id) = config.external_id {
+        validate_identifier("external id", external_id, EXTERNAL_ID_LENGTH, "_+=,.@:/-")?;
+    }
+    if let Some(ref duration_seconds) = config.duration_seconds {
+        validate_duration_seconds(duration_seconds)?;
+    }
+    if let Some(ref role_session_name) = config.role_session_name {
+        validate_identifier("role session name", role_session_name, ROLE_SESSION_NAME_LENGTH, "_+=,.@-")?;
+    }
+    if let Some(ref region) = config.region {
+        validate_region(region)?;
+    }
+    Ok(())
+}
+
+// assume role keys of a replaced profile are removed so that nothing is left over, e.g. credential_source
+// that AWS CLI refuses together with source_profile, other settings are kept
+fn set_role_settings(config_file: &Ini, profile: &str, config: &AddRoleConfig, (role_arn, source_profile): (String, String)) -> Ini {
+    let mut output = config_file.clone();
+    let section_name = get_config_section_name(config_file, profile);
+    for key in ASSUME_ROLE_KEYS.iter() {
+        output.delete_from(Some(section_name.clone()), key);
+    }
+
+    let settings = [
+        ("role_arn", Some(role_arn)),
+        ("source_profile", Some(source_profile)),
+        ("mfa_serial", config.mfa_serial.clone()),
+        ("external_id", config.external_id.clone()),
+        ("duration_seconds", config.duration_seconds.clone()),
+        ("role_session_name", config.role_session_name.clone()),
+        ("region", config.region.clone()),
+    ];
+    for (key, value) in settings.iter() {
+        if let Some(value) = value {
+            output.set_to(Some(section_name.clone()), key.to_string(), value.to_string());
+        }
+    }
+    output
+}
+
+fn select_source_profile(config_file: &Ini,
+                         credentials_file: &Ini,
+                         profile: &str,
+                         choose_profile: &mut impl FnMut(Vec<String>, &Ini, &Ini) -> Result<PickerResult, Box<dyn Error>>)
+                         -> Result<PickerResult, Box<dyn Error>> {
+    let profiles = get_all_profile_names(config_file, credentials_file).into_iter()
+        .filter(|name| name.to_lowercase() != profile.to_lowercase())
+        .collect();
+    choose_profile(profiles, config_file, credentials_file)
+}
+
+pub fn handle(config: AddRoleConfig,
+              mut read_input: impl FnMut(&str, bool) -> Result<String, Box<dyn Error>>,
+              mut choose_profile: impl FnMut(Vec<String>, &Ini, &Ini) -> Result<PickerResult, Box<dyn Error>>,
+              mut write_to_files: impl FnMut(Vec<(Ini, String)>) -> Result<(), Box<dyn Error>>)
+              -> Result<String, Box<dyn Error>> {
+    let config_file = load_ini(&config.config_path)?;
+    let credentials_file = load_ini(&config.credentials_path)?;
+
+    let profile = to_profile_name(&config.profile);
+    validate_profile_name(&profile)?;
+    validate_settings(&config)?;
+
+    let exists = profile_exists(&config_file, &credentials_file, &profile);
+    if exists && !config.force {
+        return Err(RawsError::ProfileExists { profile }.into());
+    }
+
+    let role_arn = match config.role_arn {
+        Some(ref role_arn) => role_arn.clone(),
+        None => {
+            let role_arn = read_input("Role ARN", false)?;
+            validate_role_arn(&role_arn)?;
+            role_arn
+        }
+    };
+    // nothing is added if selection is cancelled
+    let source_profile = match config.source_profile {
+        Some(ref source_profile) => to_profile_name(source_profile),
+        None => match select_source_profile(&config_file, &credentials_file, &profile, &mut choose_profile)? {
+            PickerResult::Selected(source_profile) => to_profile_name(&source_profile),
+            PickerResult::Cancelled => return Err(RawsError::PickerCancelled.into())
+        }
+    };
+
+    // source profile must exist and must not lead back to the added profile, same as when it's set as default
+    let updated_config_file = set_role_settings(&config_file, &profile, &config, (role_arn, source_profile));
+    resolve_source_profiles(&updated_config_file, &credentials_file, &profile)?;
+    write_to_files(vec!((updated_config_file, config.config_path.clone())))?;
+
+    Ok(format!("aws profile [{}] is {}", profile, if exists { "replaced" } else { "added" }))
+}
+
+#[cfg(test)]
+mod tests {
+    mod validate_role_arn {
+        use handlers::add_role::validate_role_arn;
+
+        #[test]
+        fn accept_role_arn_with_path_and_partition() {
+            assert!(validate_role_arn("arn:aws:iam::123456789012:role/some-role").is_ok());
+            assert!(validate_role_arn("arn:aws-cn:iam::123456789012:role/some/path/some-role").is_ok());
+        }
+
+        #[test]
+        fn reject_arn_that_is_not_a_role() {
+            assert!(validate_role_arn("arn:aws:iam::123456789012:user/some-user").is_err());
+            assert!(validate_role_arn("arn:aws:s3:::some-bucket").is_err());
+            assert!(validate_role_arn("arn:aws:iam::123456789012:role/").is_err());
+        }
+
+        #[test]
+        fn reject_arn_with_invalid_account_id() {
+            let result = validate_role_arn("arn:aws:iam::1234:role/some-role");
+
+            assert_eq!("invalid role arn: must look like arn:aws:iam::123456789012:role/some-role", result.unwrap_err().to_string());
+        }
+    }
+
+    mod validate_mfa_serial {
+        use handlers::add_role::validate_mfa_serial;
+
+        #[test]
+        fn accept_arn_of_virtual_device_and_serial_of_hardware_device() {
+            assert!(validate_mfa_serial("arn:aws:iam::123456789012:mfa/some-user").is_ok());
+            assert!(validate_mfa_serial("GAHT12345678").is_ok());
+        }
+
+        #[test]
+        fn reject_arn_that_is_not_an_mfa_device() {
+            assert!(validate_mfa_serial("arn:aws:iam::123456789012:role/some-role").is_err());
+        }
+    }
+
+    mod validate_duration_seconds {
+        use handlers::add_role::validate_duration_seconds;
+
+        #[test]
+        fn accept_duration_within_limits() {
+            assert!(validate_duration_seconds("900").is_ok());
+            assert!(validate_duration_seconds("43200").is_ok());
+        }
+
+        #[test]
+        fn reject_duration_out_of_limits_or_not_a_number() {
+            assert!(validate_duration_seconds("899").is_err());
+            assert!(validate_duration_seconds("1h").is_err());
+        }
+    }
+
+    mod validate_identifier {
+        use handlers::add_role::validate_identifier;
+
+        #[test]
+        fn reject_characters_other_than_given_ones() {
+            assert!(validate_identifier("role session name", "some.session@name", (2, 64), "_+=,.@-").is_ok());
+            assert!(validate_identifier("role session name", "some session", (2, 64), "_+=,.@-").is_err());
+        }
+    }
+}
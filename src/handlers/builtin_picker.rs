@@ -0,0 +1,398 @@
+use error::RawsError;
+use handlers::picker::{ Picker, PickerResult, PROMPT };
+use std::error::Error;
+
+const MAX_VISIBLE_PROFILES: usize = 10;
+
+// kind shown next to each profile, e.g. ("first_profile", "static")
+pub type ProfileKinds = Vec<(String, &'static str)>;
+
+#[derive(Debug, PartialEq)]
+enum Key {
+    Char(char),
+    Backspace,
+    ClearQuery,
+    Up,
+    Down,
+    Enter,
+    Cancel,
+}
+
+// scores how well query matches candidate as a case-insensitive subsequence, higher is better,
+// consecutive characters and characters at the start of a word score more
+fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let candidate: Vec<char> = candidate.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous_match: Option<usize> = None;
+
+    for query_char in query.to_lowercase().chars() {
+        let index = position + candidate[position..].iter().position(|c| *c == query_char)?;
+        score += 1;
+        if previous_match.is_some_and(|previous| previous + 1 == index) {
+            score += 2;
+        }
+        if index == 0 || "_- /".contains(candidate[index - 1]) {
+            score += 1;
+        }
+        previous_match = Some(index);
+        position = index + 1;
+    }
+
+    Some(score - previous_match.unwrap_or(0) as i64 / 10)
+}
+
+// profiles that are no longer in any file, e.g. old history entries, have no kind
+fn to_kind(kinds: &[(String, &'static str)], profile: &str) -> &'static str {
+    kinds.iter()
+         .find(|(name, _)| name.to_lowercase() == profile.to_lowercase())
+         .map(|(_, kind)| *kind)
+         .unwrap_or("")
+}
+
+fn parse_keys(bytes: &[u8]) -> Vec<Key> {
+    match bytes {
+        [0x1b] => vec!(Key::Cancel),
+        [0x1b, b'[', b'A'] | [0x1b, b'O', b'A'] => vec!(Key::Up),
+        [0x1b, b'[', b'B'] | [0x1b, b'O', b'B'] => vec!(Key::Down),
+        [0x1b, ..] => vec!(),
+        _ => String::from_utf8_lossy(bytes).chars().filter_map(|c| match c {
+            '\u{3}' => Some(Key::Cancel),
+            '\r' | '\n' => Some(Key::Enter),
+            '\u{7f}' | '\u{8}' => Some(Key::Backspace),
+            '\u{15}' => Some(Key::ClearQuery),
+            '\u{10}' => Some(Key::Up),
+            '\u{e}' => Some(Key::Down),
+            c if c.is_control() => None,
+            c => Some(Key::Char(c))
+        }).collect()
+    }
+}
+
+struct PickerState<'a> {
+    profiles: &'a [String],
+    kinds: &'a [(String, &'static str)],
+    query: String,
+    // indexes of profiles matching query, best match first
+    matches: Vec<usize>,
+    selected: usize,
+    offset: usize,
+}
+
+impl<'a> PickerState<'a> {
+    fn new(profiles: &'a [String], kinds: &'a [(String, &'static str)], query: &str) -> PickerState<'a> {
+        let mut state = PickerState { profiles, kinds, query: query.to_string(), matches: vec!(), selected: 0, offset: 0 };
+        state.update_matches();
+        state
+    }
+
+    fn update_matches(&mut self) {
+        let mut scored: Vec<(usize, i64)> = self.profiles.iter()
+            .enumerate()
+            .filter_map(|(index, profile)| fuzzy_score(&self.query, profile).map(|score| (index, score)))
+            .collect();
+        // sort is stable so profiles with the same score keep their original order
+        scored.sort_by(|(_, a), (_, b)| b.cmp(a));
+        self.matches = scored.into_iter().map(|(index, _)| index).collect();
+        self.selected = 0;
+        self.offset = 0;
+    }
+
+    fn move_selection(&mut self, down: bool) {
+        if self.matches.is_empty() {
+            return;
+        }
+        self.selected = if down {
+            (self.selected + 1).min(self.matches.len() - 1)
+        } else {
+            self.selected.saturating_sub(1)
+        };
+
+        if self.selected < self.offset {
+            self.offset = self.selected;
+        } else if self.selected >= self.offset + MAX_VISIBLE_PROFILES {
+            self.offset = self.selected + 1 - MAX_VISIBLE_PROFILES;
+        }
+    }
+
+    fn selected_profile(&self) -> Option<String> {
+        self.matches.get(self.selected).map(|index| self.profiles[*index].clone())
+    }
+
+    fn handle_key(&mut self, key: Key) -> Option<PickerResult> {
+        match key {
+            Key::Char(c) => {
+                self.query.push(c);
+                self.update_matches();
+            },
+            Key::Backspace => {
+                self.query.pop();
+                self.update_matches();
+            },
+            Key::ClearQuery => {
+                self.query.clear();
+                self.update_matches();
+            },
+            Key::Up => self.move_selection(false),
+            Key::Down => self.move_selection(true),
+            Key::Enter => return self.selected_profile().map(PickerResult::Selected),
+            Key::Cancel => return Some(PickerResult::Cancelled),
+        }
+        None
+    }
+
+    fn render(&self) -> Vec<String> {
+        let width = self.profiles.iter().map(|profile| profile.chars().count()).max().unwrap_or(0).max("PROFILE".len());
+        let mut lines = vec!(
+            format!("> {}", self.query),
+            format!("  {}/{}  {}", self.matches.len(), self.profiles.len(), PROMPT),
+            format!("  {:width$}  KIND", "PROFILE", width = width),
+        );
+        lines.extend(self.matches.iter()
+            .enumerate()
+            .skip(self.offset)
+            .take(MAX_VISIBLE_PROFILES)
+            .map(|(position, index)| {
+                let marker = if position == self.selected { ">" } else { " " };
+                let profile = &self.profiles[*index];
+                format!("{} {:width$}  {}", marker, profile, to_kind(self.kinds, profile), width = width).trim_end().to_string()
+            }));
+        lines
+    }
+}
+
+#[cfg(unix)]
+mod terminal {
+    use libc;
+    use std::fs::{ File, OpenOptions };
+    use std::io::{ self, Read, Write };
+    use std::mem;
+    use std::os::unix::io::AsRawFd;
+
+    // /dev/tty is used instead of stdin/stdout since stdout may be captured, e.g. eval "$(raws env)"
+    pub struct RawTerminal {
+        tty: File,
+        original: libc::termios,
+    }
+
+    impl RawTerminal {
+        pub fn open() -> io::Result<RawTerminal> {
+            let tty = OpenOptions::new().read(true).write(true).open("/dev/tty")?;
+            let fd = tty.as_raw_fd();
+            let mut original: libc::termios = unsafe { mem::zeroed() };
+            if unsafe { libc::tcgetattr(fd, &mut original) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            let mut raw = original;
+            unsafe { libc::cfmakeraw(&mut raw) };
+            if unsafe { libc::tcsetattr(fd, libc::TCSANOW, &raw) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(RawTerminal { tty, original })
+        }
+
+        pub fn read(&mut self) -> io::Result<Vec<u8>> {
+            let mut buffer = [0; 64];
+            let size = self.tty.read(&mut buffer)?;
+            Ok(buffer[..size].to_vec())
+        }
+
+        // draws lines below the cursor and moves the cursor back to the end of the first line
+        pub fn draw(&mut self, lines: &[String]) -> io::Result<()> {
+            let mut output = String::from("\r\x1b[J");
+            output.push_str(&lines.join("\r\n"));
+            if lines.len() > 1 {
+                output.push_str(&format!("\x1b[{}A", lines.len() - 1));
+            }
+            output.push_str(&format!("\r\x1b[{}C", lines[0].chars().count()));
+            self.tty.write_all(output.as_bytes())?;
+            self.tty.flush()
+        }
+
+        pub fn clear(&mut self) -> io::Result<()> {
+            self.tty.write_all(b"\r\x1b[J")?;
+            self.tty.flush()
+        }
+    }
+
+    impl Drop for RawTerminal {
+        fn drop(&mut self) {
+            unsafe { libc::tcsetattr(self.tty.as_raw_fd(), libc::TCSANOW, &self.original) };
+        }
+    }
+}
+
+fn unavailable(reason: impl ToString) -> Box<dyn Error> {
+    RawsError::PickerUnavailable { picker: "builtin".to_string(), reason: reason.to_string() }.into()
+}
+
+#[cfg(unix)]
+fn run(state: &mut PickerState) -> Result<PickerResult, Box<dyn Error>> {
+    let mut terminal = terminal::RawTerminal::open().map_err(unavailable)?;
+    let outcome = 'picking: loop {
+        terminal.draw(&state.render())?;
+        let bytes = terminal.read()?;
+        if bytes.is_empty() {
+            break PickerResult::Cancelled;
+        }
+        for key in parse_keys(&bytes) {
+            if let Some(outcome) = state.handle_key(key) {
+                break 'picking outcome;
+            }
+        }
+    };
+    terminal.clear()?;
+    Ok(outcome)
+}
+
+#[cfg(not(unix))]
+fn run(_state: &mut PickerState) -> Result<PickerResult, Box<dyn Error>> {
+    Err(unavailable("terminal is not supported on this platform"))
+}
+
+// same behaviour as fzf with `-1 -0`: returns the only match of initial query without asking
+// and cancels if initial query doesn't match any profile
+pub fn choose_profile(profiles: Vec<String>, kinds: &[(String, &'static str)], query: &str) -> Result<PickerResult, Box<dyn Error>> {
+    let mut state = PickerState::new(&profiles, kinds, query);
+    match state.selected_profile() {
+        None => Ok(PickerResult::Cancelled),
+        Some(profile) if state.matches.len() == 1 => Ok(PickerResult::Selected(profile)),
+        _ => run(&mut state)
+    }
+}
+
+pub struct BuiltinPicker {
+    pub kinds: ProfileKinds,
+}
+
+impl Picker for BuiltinPicker {
+    fn choose(&self, profiles: Vec<String>, query: &str) -> Result<PickerResult, Box<dyn Error>> {
+        choose_profile(profiles, &self.kinds, query)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use handlers::builtin_picker::ProfileKinds;
+
+    fn get_profiles() -> Vec<String> {
+        vec!("first_profile".to_string(), "second_profile".to_string(), "first_assumed_profile".to_string())
+    }
+
+    fn get_kinds() -> ProfileKinds {
+        vec!(("first_profile".to_string(), "static"), ("first_assumed_profile".to_string(), "assume-role"))
+    }
+
+    mod fuzzy_score {
+        use handlers::builtin_picker::fuzzy_score;
+
+        #[test]
+        fn return_none_if_query_is_not_a_subsequence() {
+            assert!(fuzzy_score("fsx", "first_profile").is_none());
+        }
+
+        #[test]
+        fn match_ignoring_case() {
+            assert!(fuzzy_score("FIRST", "first_profile").is_some());
+        }
+
+        #[test]
+        fn score_consecutive_characters_higher() {
+            let consecutive = fuzzy_score("sec", "second_profile").unwrap();
+            let scattered = fuzzy_score("sec", "some_extra_profile_c").unwrap();
+
+            assert!(consecutive > scattered);
+        }
+    }
+
+    mod parse_keys {
+        use handlers::builtin_picker::{ parse_keys, Key };
+
+        #[test]
+        fn parse_arrow_keys_and_escape() {
+            assert_eq!(vec!(Key::Up), parse_keys(b"\x1b[A"));
+            assert_eq!(vec!(Key::Down), parse_keys(b"\x1bOB"));
+            assert_eq!(vec!(Key::Cancel), parse_keys(b"\x1b"));
+        }
+
+        #[test]
+        fn parse_every_character_of_pasted_text() {
+            assert_eq!(vec!(Key::Char('a'), Key::Char('é'), Key::Backspace, Key::Enter), parse_keys("aé\u{7f}\r".as_bytes()));
+        }
+    }
+
+    mod picker_state {
+        use handlers::builtin_picker::{ Key, PickerState };
+        use handlers::picker::PickerResult;
+
+        #[test]
+        fn filter_profiles_while_typing() {
+            let (profiles, kinds) = (super::get_profiles(), super::get_kinds());
+            let mut state = PickerState::new(&profiles, &kinds, "");
+
+            state.handle_key(Key::Char('a'));
+            state.handle_key(Key::Char('s'));
+
+            assert_eq!(Some("first_assumed_profile".to_string()), state.selected_profile());
+            assert_eq!(1, state.matches.len());
+        }
+
+        #[test]
+        fn select_profile_with_arrow_keys() {
+            let (profiles, kinds) = (super::get_profiles(), super::get_kinds());
+            let mut state = PickerState::new(&profiles, &kinds, "");
+
+            state.handle_key(Key::Down);
+            state.handle_key(Key::Down);
+            state.handle_key(Key::Down);
+            state.handle_key(Key::Up);
+            let outcome = state.handle_key(Key::Enter);
+
+            assert_eq!(Some(PickerResult::Selected("second_profile".to_string())), outcome);
+        }
+
+        #[test]
+        fn ignore_enter_if_nothing_matches() {
+            let (profiles, kinds) = (super::get_profiles(), super::get_kinds());
+            let mut state = PickerState::new(&profiles, &kinds, "xyz");
+
+            assert!(state.handle_key(Key::Enter).is_none());
+            assert_eq!(Some(PickerResult::Cancelled), state.handle_key(Key::Cancel));
+        }
+
+        #[test]
+        fn render_header_with_profile_kind() {
+            let (profiles, kinds) = (super::get_profiles(), super::get_kinds());
+            let state = PickerState::new(&profiles, &kinds, "first");
+
+            let expected = [
+                "> first",
+                "  2/3  Select AWS profile",
+                "  PROFILE                KIND",
+                "> first_profile          static",
+                "  first_assumed_profile  assume-role",
+            ];
+            assert_eq!(expected.to_vec(), state.render());
+        }
+    }
+
+    mod choose_profile {
+        use handlers::builtin_picker;
+        use handlers::picker::PickerResult;
+
+        #[test]
+        fn return_only_match_of_initial_query_without_asking() {
+            let result = builtin_picker::choose_profile(super::get_profiles(), &super::get_kinds(), "second");
+
+            assert_eq!(PickerResult::Selected("second_profile".to_string()), result.unwrap());
+        }
+
+        #[test]
+        fn return_cancelled_if_initial_query_matches_no_profile() {
+            let result = builtin_picker::choose_profile(super::get_profiles(), &super::get_kinds(), "xyz");
+
+            assert_eq!(PickerResult::Cancelled, result.unwrap());
+        }
+    }
+}
@@ -1,9 +1,15 @@
-use ini::ini::Properties;
-use ini::Ini;
-use ini::ini::Error::{ Io, Parse };
+use aws_ini::Properties;
+use aws_ini::Ini;
+use error::RawsError;
+use std::fs;
+use std::io::ErrorKind;
 use shellexpand::tilde;
 
-pub fn find_profile_with_name<'a>(file: &'a Ini, selected_profile: &String) -> Option<(&'a Option<String>, &'a Properties)> {
+pub const STATIC_KIND: &str = "static";
+pub const ASSUME_ROLE_KIND: &str = "assume-role";
+const CONFIG_SECTION_PREFIX: &str = "profile ";
+
+pub fn find_profile_with_name<'a>(file: &'a Ini, selected_profile: &str) -> Option<(&'a Option<String>, &'a Properties)> {
     file.iter().find(|(section, _)|
         match section {
             Some(section_name) => section_name.to_lowercase() == selected_profile.to_lowercase(),
@@ -12,6 +18,44 @@ pub fn find_profile_with_name<'a>(file: &'a Ini, selected_profile: &String) -> O
     )
 }
 
+// profiles other than default are named `[profile name]` in config file and `[name]` in credentials file,
+// names shown to and accepted from users never have the prefix so that a profile has the same name in both files
+pub fn to_profile_name(section_name: &str) -> String {
+    match section_name.get(..CONFIG_SECTION_PREFIX.len()) {
+        Some(prefix) if prefix.eq_ignore_ascii_case(CONFIG_SECTION_PREFIX) => section_name[CONFIG_SECTION_PREFIX.len()..].to_string(),
+        _ => section_name.to_string()
+    }
+}
+
+pub fn to_config_section_name(name: &str) -> String {
+    let name = to_profile_name(name);
+    if name.to_lowercase() == "default" { name } else { format!("{}{}", CONFIG_SECTION_PREFIX, name) }
+}
+
+pub fn find_config_profile<'a>(config_file: &'a Ini, name: &str) -> Option<&'a Properties> {
+    find_profile_with_name(config_file, &to_config_section_name(name)).map(get_value_of_tuple)
+}
+
+pub fn find_credentials_profile<'a>(credentials_file: &'a Ini, name: &str) -> Option<&'a Properties> {
+    find_profile_with_name(credentials_file, &to_profile_name(name)).map(get_value_of_tuple)
+}
+
+// existing sections are matched case-insensitively, so a section is only named after the given profile name
+// if the file doesn't have one yet
+fn get_section_name(file: &Ini, section_name: String) -> String {
+    find_profile_with_name(file, &section_name)
+        .and_then(|(section, _)| section.clone())
+        .unwrap_or(section_name)
+}
+
+pub fn get_config_section_name(config_file: &Ini, name: &str) -> String {
+    get_section_name(config_file, to_config_section_name(name))
+}
+
+pub fn get_credentials_section_name(credentials_file: &Ini, name: &str) -> String {
+    get_section_name(credentials_file, to_profile_name(name))
+}
+
 pub fn get_assume_settings(properties: &Properties) -> Option<(&String, &String)> {
     let role_arn = properties.get("role_arn");
     let source_profile = properties.get("source_profile");
@@ -26,25 +70,31 @@ pub fn get_value_of_tuple<'a>((_, properties): (&Option<String>, &'a Properties)
     properties
 }
 
-pub fn load_ini(path: &String) -> Result<Ini, String> {
+// a missing file is treated as empty (e.g. no credentials file with SSO-only setups) and is created on write,
+// errors show the resolved path since it may come from a flag, an environment variable or the default
+pub fn load_ini(path: &String) -> Result<Ini, RawsError> {
     let expanded_path = tilde(path).to_string();
 
-    match Ini::load_from_file(expanded_path) {
-        Ok(f) => Ok(f),
-        Err(Io(_)) => Err(format!("failed to load file {}", path)),
-        Err(Parse(_)) => Err(format!("invalid file {}", path)),
-    }
-}
-
-pub fn compose<A, B, C>(fn1: impl Fn(A) -> B, fn2: impl Fn(B) -> C) -> impl Fn(A) -> C {
-    move |b| fn2(fn1(b))
+    let content = match fs::read_to_string(&expanded_path) {
+        Ok(content) => content,
+        Err(ref e) if e.kind() == ErrorKind::NotFound => return Ok(Ini::new()),
+        Err(e) => return Err(RawsError::read_failed(&expanded_path, &e))
+    };
+    Ini::load_from_str(&content).map_err(|e| RawsError::ParseError {
+        path: expanded_path,
+        line: e.line,
+        column: e.column,
+        text: e.text,
+        message: e.message,
+        hint: e.hint,
+    })
 }
 
 #[cfg(test)]
 mod tests {
     mod find_profile_with_name {
         use handlers::common;
-        use ini::Ini;
+        use aws_ini::Ini;
 
         #[test]
         fn return_none_if_profile_not_found() {
@@ -74,8 +124,70 @@ mod tests {
         }
     }
 
+    mod to_profile_name {
+        use handlers::common;
+
+        #[test]
+        fn strip_profile_prefix_from_config_section_name() {
+            assert_eq!("first_assumed_profile", common::to_profile_name("profile first_assumed_profile"));
+            assert_eq!("First_Assumed", common::to_profile_name("PROFILE First_Assumed"));
+        }
+
+        #[test]
+        fn return_section_name_if_there_is_no_profile_prefix() {
+            assert_eq!("first_profile", common::to_profile_name("first_profile"));
+            assert_eq!("profile_without_space", common::to_profile_name("profile_without_space"));
+        }
+    }
+
+    mod find_config_profile {
+        use aws_ini::Ini;
+        use handlers::common;
+
+        fn get_test_ini() -> Ini {
+            let mut conf = Ini::new();
+            conf.with_section(Some("default")).set("region", "us-east-1");
+            conf.with_section(Some("profile first")).set("region", "eu-west-1");
+            conf
+        }
+
+        #[test]
+        fn find_profile_with_or_without_prefix() {
+            let conf = get_test_ini();
+
+            assert_eq!(Some("eu-west-1"), common::find_config_profile(&conf, "first").and_then(|p| p.get("region")).map(|r| r.as_str()));
+            assert_eq!(Some("eu-west-1"), common::find_config_profile(&conf, "profile first").and_then(|p| p.get("region")).map(|r| r.as_str()));
+        }
+
+        #[test]
+        fn find_default_profile_without_prefix() {
+            let conf = get_test_ini();
+
+            assert_eq!(Some("us-east-1"), common::find_config_profile(&conf, "default").and_then(|p| p.get("region")).map(|r| r.as_str()));
+        }
+    }
+
+    mod get_config_section_name {
+        use handlers::common;
+        use aws_ini::Ini;
+
+        #[test]
+        fn return_name_of_existing_section_ignoring_case() {
+            let mut conf = Ini::new();
+            conf.with_section(Some("profile First")).set("region", "eu-west-1");
+
+            assert_eq!("profile First", common::get_config_section_name(&conf, "first"));
+        }
+
+        #[test]
+        fn return_prefixed_name_if_section_not_found() {
+            assert_eq!("profile first", common::get_config_section_name(&Ini::new(), "first"));
+            assert_eq!("default", common::get_config_section_name(&Ini::new(), "default"));
+        }
+    }
+
     mod get_assume_settings {
-        use ini::ini::Properties;
+        use aws_ini::Properties;
         use handlers::common;
 
         #[test]
@@ -110,4 +222,31 @@ mod tests {
             assert_eq!(result, Some((&"some_arn".to_string(), &"some_profile".to_string())));
         }
     }
+
+    mod load_ini {
+        use handlers::common;
+        use shellexpand::tilde;
+        use std::env::temp_dir;
+        use std::fs;
+        use std::process;
+
+        #[test]
+        fn return_empty_ini_if_file_not_found() {
+            let result = common::load_ini(&"~/raws_not_existing_config".to_string());
+
+            assert_eq!(0, result.unwrap().iter().count());
+        }
+
+        #[test]
+        fn show_resolved_path_if_file_is_invalid() {
+            let path = temp_dir().join(format!("raws_{}_invalid_config", process::id())).display().to_string();
+            fs::write(&path, "[default\n").unwrap();
+
+            let result = common::load_ini(&path);
+
+            let error_message = result.unwrap_err().to_string();
+            assert!(error_message.starts_with(&format!("invalid file {}", tilde(&path))));
+            assert!(error_message.contains("line 1"));
+        }
+    }
 }
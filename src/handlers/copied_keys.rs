@@ -0,0 +1,127 @@
+use handlers::common::find_config_profile;
+use handlers::set::ASSUME_ROLE_KEYS;
+use aws_ini::Properties;
+use aws_ini::Ini;
+
+// raws records the keys it copied into default profile that are not assume role keys, e.g. region, in a file
+// next to AWS config file together with the values of default profile they replaced
+const COPIED_SECTION: &str = "copied";
+const REPLACED_SECTION: &str = "replaced";
+
+fn sorted_keys(properties: &Properties) -> Vec<&String> {
+    let mut keys: Vec<&String> = properties.keys().collect();
+    keys.sort();
+    keys
+}
+
+// puts back the values of default profile that copied keys replaced and removes the copied keys that didn't
+// replace any, copied values that the user changed since are left as they are
+pub fn revert_copied_keys(config_file: &Ini, copied_keys_file: &Ini) -> Ini {
+    let mut output = config_file.clone();
+    let default_properties = find_config_profile(config_file, "default");
+    let replaced = copied_keys_file.section(Some(REPLACED_SECTION));
+
+    if let Some(copied) = copied_keys_file.section(Some(COPIED_SECTION)) {
+        for key in sorted_keys(copied) {
+            if default_properties.and_then(|properties| properties.get(key)) != copied.get(key) {
+                continue;
+            }
+            match replaced.and_then(|replaced| replaced.get(key)) {
+                Some(value) => output.set_to(Some("default"), key.to_string(), value.to_string()),
+                None => { output.delete_from(Some("default"), key); }
+            }
+        }
+    }
+    output
+}
+
+// copies the given keys that are not assume role keys from the selected profile to default profile,
+// returns the updated config file and the record of copied keys
+pub fn copy_keys(config_file: &Ini, properties: &Properties, keys: &[String]) -> (Ini, Ini) {
+    let mut output = config_file.clone();
+    let mut copied: Vec<(&String, &String)> = vec!();
+    let mut replaced: Vec<(&String, &String)> = vec!();
+    let default_properties = find_config_profile(config_file, "default");
+
+    for key in keys.iter().filter(|key| !ASSUME_ROLE_KEYS.contains(&key.as_str())) {
+        let value = match properties.get(key) {
+            Some(value) => value,
+            None => continue
+        };
+        let default_value = default_properties.and_then(|properties| properties.get(key));
+        if default_value == Some(value) {
+            continue;
+        }
+
+        if let Some(default_value) = default_value {
+            replaced.push((key, default_value));
+        }
+        copied.push((key, value));
+        output.set_to(Some("default"), key.to_string(), value.to_string());
+    }
+
+    let mut copied_keys_file = Ini::new();
+    for (section, properties) in [(COPIED_SECTION, copied), (REPLACED_SECTION, replaced)] {
+        for (key, value) in properties {
+            copied_keys_file.with_section(Some(section)).set(key.to_string(), value.to_string());
+        }
+    }
+    (output, copied_keys_file)
+}
+
+#[cfg(test)]
+mod tests {
+    mod revert_copied_keys {
+        use aws_ini::Ini;
+        use handlers::copied_keys;
+
+        fn get_copied_keys_file() -> Ini {
+            let mut copied_keys_file = Ini::new();
+            copied_keys_file.with_section(Some("copied")).set("region", "eu-west-1").set("output", "text");
+            copied_keys_file.with_section(Some("replaced")).set("region", "ap-southeast-2");
+            copied_keys_file
+        }
+
+        #[test]
+        fn put_back_replaced_values_and_remove_other_copied_keys() {
+            let mut conf = Ini::new();
+            conf.with_section(Some("default")).set("region", "eu-west-1").set("output", "text").set("cli_pager", "less");
+
+            let updated_conf = copied_keys::revert_copied_keys(&conf, &get_copied_keys_file());
+
+            assert_eq!("[default]\nregion = ap-southeast-2\ncli_pager = less\n", updated_conf.to_string());
+        }
+
+        #[test]
+        fn keep_copied_keys_changed_by_user() {
+            let mut conf = Ini::new();
+            conf.with_section(Some("default")).set("region", "us-west-2").set("output", "json");
+
+            let updated_conf = copied_keys::revert_copied_keys(&conf, &get_copied_keys_file());
+
+            assert_eq!("[default]\nregion = us-west-2\noutput = json\n", updated_conf.to_string());
+        }
+    }
+
+    mod copy_keys {
+        use aws_ini::{ Ini, Properties };
+        use handlers::copied_keys;
+
+        #[test]
+        fn record_copied_keys_and_replaced_values_of_default_profile() {
+            let mut conf = Ini::new();
+            conf.with_section(Some("default")).set("region", "ap-southeast-2").set("output", "json");
+            let mut properties = Properties::new();
+            properties.insert("role_arn".to_string(), "arn".to_string());
+            properties.insert("region".to_string(), "eu-west-1".to_string());
+            properties.insert("output".to_string(), "json".to_string());
+            properties.insert("cli_pager".to_string(), "less".to_string());
+            let keys = ["role_arn".to_string(), "region".to_string(), "output".to_string(), "cli_pager".to_string()];
+
+            let (updated_conf, copied_keys_file) = copied_keys::copy_keys(&conf, &properties, &keys);
+
+            assert_eq!("[default]\nregion = eu-west-1\noutput = json\ncli_pager = less\n", updated_conf.to_string());
+            assert_eq!("[copied]\nregion = eu-west-1\ncli_pager = less\n\n[replaced]\nregion = ap-southeast-2\n", copied_keys_file.to_string());
+        }
+    }
+}
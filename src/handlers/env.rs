@@ -0,0 +1,121 @@
+use handlers::common::{ find_config_profile, find_credentials_profile, to_profile_name };
+use handlers::common::get_assume_settings;
+use handlers::common::load_ini;
+use handlers::picker::PickerResult;
+use handlers::set::{ get_profile_settings, select_profile };
+use aws_ini::Ini;
+use config::{ EnvConfig, Shell };
+use error::RawsError;
+use std::error::Error;
+
+const AWS_PROFILE: &str = "AWS_PROFILE";
+const AWS_ACCESS_KEY_ID: &str = "AWS_ACCESS_KEY_ID";
+const AWS_SECRET_ACCESS_KEY: &str = "AWS_SECRET_ACCESS_KEY";
+const AWS_SESSION_TOKEN: &str = "AWS_SESSION_TOKEN";
+
+// None means the variable is unset so that it doesn't take precedence over the selected profile
+type Variables = Vec<(&'static str, Option<String>)>;
+
+fn get_assume_variables(config_file: &Ini, selected_profile: &str) -> Option<Variables> {
+    find_config_profile(config_file, selected_profile)
+        .and_then(get_assume_settings)
+        .map(|_| vec!(
+            (AWS_PROFILE, Some(to_profile_name(selected_profile))),
+            (AWS_ACCESS_KEY_ID, None),
+            (AWS_SECRET_ACCESS_KEY, None),
+            (AWS_SESSION_TOKEN, None),
+        ))
+}
+
+fn get_static_variables(credentials_file: &Ini, selected_profile: &str) -> Option<Variables> {
+    let properties = find_credentials_profile(credentials_file, selected_profile)?;
+    get_profile_settings(properties)
+        .map(|(aws_access_key_id, aws_secret_access_key)| vec!(
+            (AWS_PROFILE, Some(to_profile_name(selected_profile))),
+            (AWS_ACCESS_KEY_ID, Some(aws_access_key_id.to_string())),
+            (AWS_SECRET_ACCESS_KEY, Some(aws_secret_access_key.to_string())),
+            (AWS_SESSION_TOKEN, properties.get("aws_session_token").cloned()),
+        ))
+}
+
+fn quote(value: &str, shell: &Shell) -> String {
+    match shell {
+        Shell::Posix => format!("'{}'", value.replace('\'', "'\\''")),
+        Shell::Fish => format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'")),
+        Shell::PowerShell => format!("'{}'", value.replace('\'', "''")),
+    }
+}
+
+fn format_variable((name, value): &(&str, Option<String>), shell: &Shell) -> String {
+    match (shell, value) {
+        (Shell::Posix, Some(value)) => format!("export {}={}", name, quote(value, shell)),
+        (Shell::Posix, None) => format!("unset {}", name),
+        (Shell::Fish, Some(value)) => format!("set -gx {} {};", name, quote(value, shell)),
+        (Shell::Fish, None) => format!("set -e {};", name),
+        (Shell::PowerShell, Some(value)) => format!("$env:{} = {}", name, quote(value, shell)),
+        (Shell::PowerShell, None) => format!("Remove-Item Env:{} -ErrorAction SilentlyContinue", name),
+    }
+}
+
+fn format_variables(variables: &Variables, shell: &Shell) -> String {
+    variables.iter()
+        .map(|variable| format_variable(variable, shell))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+pub fn handle(config: EnvConfig,
+              mut choose_profile: impl FnMut(Vec<String>, &Ini, &Ini) -> Result<PickerResult, Box<dyn Error>>)
+              -> Result<String, Box<dyn Error>> {
+    let config_file = load_ini(&config.config_path)?;
+    let credentials_file = load_ini(&config.credentials_path)?;
+
+    let selected_profile = match select_profile(&config_file, &credentials_file, &config.pattern, &mut choose_profile)? {
+        PickerResult::Selected(profile) => profile,
+        PickerResult::Cancelled => return Err(RawsError::PickerCancelled.into())
+    };
+
+    let variables = get_assume_variables(&config_file, &selected_profile)
+        .or_else(|| get_static_variables(&credentials_file, &selected_profile));
+
+    match variables {
+        Some(variables) => Ok(format_variables(&variables, &config.shell)),
+        None => Err(RawsError::ProfileNotFound { profile: selected_profile }.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    mod format_variables {
+        use handlers::env;
+        use config::Shell;
+
+        fn get_test_variables() -> env::Variables {
+            vec!(
+                ("AWS_PROFILE", Some("it's".to_string())),
+                ("AWS_SESSION_TOKEN", None),
+            )
+        }
+
+        #[test]
+        fn format_posix_shell_commands() {
+            let output = env::format_variables(&get_test_variables(), &Shell::Posix);
+
+            assert_eq!("export AWS_PROFILE='it'\\''s'\nunset AWS_SESSION_TOKEN", output);
+        }
+
+        #[test]
+        fn format_fish_shell_commands() {
+            let output = env::format_variables(&get_test_variables(), &Shell::Fish);
+
+            assert_eq!("set -gx AWS_PROFILE 'it\\'s';\nset -e AWS_SESSION_TOKEN;", output);
+        }
+
+        #[test]
+        fn format_powershell_commands() {
+            let output = env::format_variables(&get_test_variables(), &Shell::PowerShell);
+
+            assert_eq!("$env:AWS_PROFILE = 'it''s'\nRemove-Item Env:AWS_SESSION_TOKEN -ErrorAction SilentlyContinue", output);
+        }
+    }
+}
@@ -1,36 +1,36 @@
-use std::process::Child;
-use std::process::{Command, Stdio};
-use std::io::{Write};
-use std::error::Error;
+use handlers::picker::{ CommandPicker, PROMPT, QUERY_PLACEHOLDER };
 
-fn to_string_without_whitespace(input: Vec<u8>) -> Result<String, Box<Error>> {
-    Ok(String::from(String::from_utf8(input).unwrap().trim_end()))
-}
+// fzf exits with 1 if there's no match and 130 if it's interrupted with Ctrl-C or Esc
+const FZF_NO_MATCH_EXIT_CODE: i32 = 1;
+const FZF_INTERRUPTED_EXIT_CODE: i32 = 130;
 
-fn spawn_fzf_command() -> std::io::Result<Child> {
-    Command::new("fzf")
-            .args(&[
-                "--height", "30%",
-                "--reverse",
-                "-1",
-                "-0",
-                "--header", "'Select AWS profile'"
-            ])
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .spawn()
+pub fn picker(preview_command: &Option<String>) -> CommandPicker {
+    let mut picker = CommandPicker::new("fzf",
+                                        &["--height", "30%", "--reverse", "-1", "-0", "--header", PROMPT, "--query", QUERY_PLACEHOLDER],
+                                        &[FZF_NO_MATCH_EXIT_CODE, FZF_INTERRUPTED_EXIT_CODE]);
+    if let Some(preview_command) = preview_command {
+        picker.args.extend(vec!("--preview".to_string(), preview_command.clone(), "--preview-window".to_string(), "right:60%".to_string()));
+    }
+    picker
 }
 
-fn write_to_fzf_stdin(fzf_command: &mut Child, profiles: Vec<String>) -> Result<(), Box<Error>> {
-    let fzf_stdin = fzf_command.stdin.as_mut().ok_or(String::from("failed to access fzf stdin"))?;
-    fzf_stdin.write_all(profiles.join("\n").as_bytes()).map_err(|e| e.into())
-}
+#[cfg(test)]
+mod tests {
+    mod picker {
+        use handlers::fzf;
+
+        #[test]
+        fn pass_preview_command_to_fzf() {
+            let picker = fzf::picker(&Some("raws describe {}".to_string()));
+
+            assert!(picker.args.windows(2).any(|args| args == ["--preview", "raws describe {}"]));
+        }
 
-pub fn choose_profile(profiles: Vec<String>) -> Result<String, Box<Error>> {
-    let mut fzf_command = spawn_fzf_command()?;
-    write_to_fzf_stdin(&mut fzf_command, profiles)?;
+        #[test]
+        fn leave_out_preview_if_there_is_no_preview_command() {
+            let picker = fzf::picker(&None);
 
-    let output = fzf_command.wait_with_output()?;
-    let output_value = if output.status.success()  { output.stdout } else { output.stderr };
-    to_string_without_whitespace(output_value)
+            assert!(!picker.args.contains(&"--preview".to_string()));
+        }
+    }
 }
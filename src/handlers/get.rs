@@ -1,16 +1,21 @@
 use handlers::common::find_profile_with_name;
 use handlers::common::get_value_of_tuple;
 use handlers::common::get_assume_settings;
-use handlers::common::{ load_ini, compose };
-use ini::ini::Properties;
-use ini::Ini;
-use config::{ GetConfig };
+use handlers::common::{ load_ini, to_profile_name };
+use aws_ini::Properties;
+use aws_ini::Ini;
+use config::GetConfig;
+use error::RawsError;
 use std::error::Error;
 
 fn get_access_key_id(properties: &Properties) -> Option<&String> {
     properties.get("aws_access_key_id")
 }
 
+fn get_session_token(properties: &Properties) -> Option<&String> {
+    properties.get("aws_session_token")
+}
+
 fn section_has_same_access_key_id(default_access_key_id: &String, properties: &Properties) -> bool {
     match get_access_key_id(properties) {
         Some(value) => value == default_access_key_id,
@@ -18,16 +23,19 @@ fn section_has_same_access_key_id(default_access_key_id: &String, properties: &P
     }
 }
 
-fn find_section_with_same_access_key<'a>(credentials_file: &'a Ini) -> impl Fn(&String) -> Option<(&'a Option<String>, &'a Properties)> {
+type Section<'a> = (&'a Option<String>, &'a Properties);
+
+const STATIC_KEYS: [&str; 2] = ["aws_access_key_id", "aws_secret_access_key"];
+
+fn find_sections_with_same_access_key<'a>(credentials_file: &'a Ini) -> impl Fn(&String) -> Vec<Section<'a>> {
     move |default_access_key_id: &String| {
-            credentials_file.iter().find(|(section, properties)| {
+        credentials_file.iter().filter(|(section, properties)| {
             section_is_not_default(section) &&
             section_has_same_access_key_id(default_access_key_id, properties)
-        })
+        }).collect()
     }
 }
 
-
 fn section_has_same_assume_settings(default_assume_settings: (&String, &String), properties: &Properties) -> bool {
     match get_assume_settings(properties) {
         Some(settings) => default_assume_settings == settings,
@@ -35,15 +43,26 @@ fn section_has_same_assume_settings(default_assume_settings: (&String, &String),
     }
 }
 
-fn find_section_with_same_assume_settings<'a>(config_file: &'a Ini) -> impl Fn((&String, &String)) -> Option<(&'a Option<String>, &'a Properties)> {
+fn find_sections_with_same_assume_settings<'a>(config_file: &'a Ini) -> impl Fn((&String, &String)) -> Vec<Section<'a>> {
     move |default_assume_settings: (&String, &String)| {
-            config_file.iter().find(|(section, properties)| {
+        config_file.iter().filter(|(section, properties)| {
             section_is_not_default(section) &&
-            section_has_same_assume_settings(default_assume_settings, &properties)
-        })
+            section_has_same_assume_settings(default_assume_settings, properties)
+        }).collect()
     }
 }
 
+// keeps only the candidates having the same values as default profile for all given keys,
+// falls back to all candidates if none of them matches completely
+fn narrow_by_matching_keys<'a, K: AsRef<str>>(candidates: Vec<Section<'a>>, default_properties: &Properties, keys: &[K]) -> Vec<Section<'a>> {
+    let matching: Vec<Section<'a>> = candidates.iter()
+        .filter(|(_, properties)| keys.iter().all(|key| properties.get(key.as_ref()) == default_properties.get(key.as_ref())))
+        .cloned()
+        .collect();
+
+    if matching.is_empty() { candidates } else { matching }
+}
+
 fn section_is_not_default(section: &Option<String>) -> bool {
     match section {
         Some(name) => name.to_lowercase() != "default",
@@ -51,46 +70,68 @@ fn section_is_not_default(section: &Option<String>) -> bool {
     }
 }
 
-fn find_current_assume_profile<'a>(file: &'a Ini) -> Option<(&'a Option<String>, &Properties)> {
-    find_profile_with_name(&file, &String::from("default"))
-        .and_then(compose(get_value_of_tuple, get_assume_settings))
-        .and_then(find_section_with_same_assume_settings(&file))
+fn find_current_assume_profiles<'a>(file: &'a Ini, copy_keys: &[String]) -> Vec<Section<'a>> {
+    find_profile_with_name(file, "default")
+        .map(get_value_of_tuple)
+        .and_then(|default_properties| get_assume_settings(default_properties)
+            .map(find_sections_with_same_assume_settings(file))
+            .map(|candidates| narrow_by_matching_keys(candidates, default_properties, copy_keys)))
+        .unwrap_or_default()
 }
 
-fn find_current_profile<'a>(file: &'a Ini) -> Option<(&'a Option<String>, &Properties)> {
-    find_profile_with_name(&file, &String::from("default"))
-        .and_then(compose(get_value_of_tuple, get_access_key_id))
-        .and_then(find_section_with_same_access_key(&file))
+fn find_current_profiles(file: &Ini) -> Vec<Section<'_>> {
+    find_profile_with_name(file, "default")
+        .map(get_value_of_tuple)
+        .and_then(|default_properties| get_access_key_id(default_properties)
+            .map(find_sections_with_same_access_key(file))
+            // a session token is only valid with the keys it was issued with, default profile with a token of
+            // another profile doesn't match any profile
+            .map(|candidates| candidates.into_iter()
+                                        .filter(|(_, properties)| get_session_token(properties) == get_session_token(default_properties))
+                                        .collect())
+            .map(|candidates| narrow_by_matching_keys(candidates, default_properties, &STATIC_KEYS)))
+        .unwrap_or_default()
 }
 
-fn get_section_name((section_name, _): (&Option<String>, &Properties)) -> Option<String> {
-    match section_name {
-        Some(name) => Some(name.to_string()),
-        None => None
+fn get_section_name((section_name, _): Section) -> Option<String> {
+    section_name.as_ref().map(|name| to_profile_name(name))
+}
+
+// assume role profiles are told apart by the keys copied to default profile
+pub fn find_current_profile_names(config_file: &Ini, credentials_file: &Ini, copy_keys: &[String]) -> Vec<String> {
+    let assume_profiles = find_current_assume_profiles(config_file, copy_keys);
+    let profiles = if assume_profiles.is_empty() { find_current_profiles(credentials_file) } else { assume_profiles };
+    profiles.into_iter().filter_map(get_section_name).collect()
+}
+
+// current profile is only known if default profile matches exactly one profile
+pub fn find_current_profile_name(config_file: &Ini, credentials_file: &Ini, copy_keys: &[String]) -> Option<String> {
+    match find_current_profile_names(config_file, credentials_file, copy_keys).as_slice() {
+        [name] => Some(name.clone()),
+        _ => None
     }
 }
 
-pub fn handle(config: GetConfig) -> Result<String, Box<Error>> {
+pub fn handle(config: GetConfig) -> Result<String, Box<dyn Error>> {
     let config_file = load_ini(&config.config_path)?;
     let credentials_file = load_ini(&config.credentials_path)?;
 
-    let section_name = find_current_assume_profile(&config_file)
-        .or_else(|| find_current_profile(&credentials_file))
-        .and_then(get_section_name);
-
-    match section_name {
-        Some(name) => Ok(name),
-        None => Err(String::from("no default profile set").into())
+    let names = find_current_profile_names(&config_file, &credentials_file, &config.copy_keys);
+    match (names.as_slice(), config.all) {
+        ([], _) => Err(RawsError::Other(String::from("no default profile set")).into()),
+        ([name], _) => Ok(name.clone()),
+        (_, true) => Ok(names.join("\n")),
+        (_, false) => Err(RawsError::AmbiguousProfile { profiles: names }.into())
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use ini::ini::Properties;
+    use aws_ini::Properties;
 
-    mod find_section_with_same_assume_settings {
+    mod find_sections_with_same_assume_settings {
         use handlers::get;
-        use ini::Ini;
+        use aws_ini::Ini;
 
         fn get_test_ini() -> Ini {
             let mut conf = Ini::new();
@@ -100,22 +141,64 @@ mod tests {
             conf.with_section(Some("second_section".to_string()))
                 .set("role_arn", "arn_2")
                 .set("source_profile", "source_profile_2");
+            conf.with_section(Some("third_section".to_string()))
+                .set("role_arn", "arn_2")
+                .set("source_profile", "source_profile_2")
+                .set("region", "us-east-1");
             conf
         }
 
         #[test]
-        fn return_none_if_not_found() {
+        fn return_empty_if_not_found() {
             let conf = get_test_ini();
-            let result = get::find_section_with_same_assume_settings(&conf)((&"arn_3".to_string(), &"source_profile_3".to_string()));
-            assert!(result.is_none());
+            let result = get::find_sections_with_same_assume_settings(&conf)((&"arn_3".to_string(), &"source_profile_3".to_string()));
+            assert!(result.is_empty());
         }
 
         #[test]
-        fn return_some_if_found() {
+        fn return_all_sections_if_found() {
             let conf = get_test_ini();
-            let result = get::find_section_with_same_assume_settings(&conf)((&"arn_2".to_string(), &"source_profile_2".to_string()));
-            assert!(result.is_some());
-            super::assert_section_name(result, "second_section");
+            let result = get::find_sections_with_same_assume_settings(&conf)((&"arn_2".to_string(), &"source_profile_2".to_string()));
+            super::assert_section_names(result, &["second_section", "third_section"]);
+        }
+    }
+
+    mod narrow_by_matching_keys {
+        use handlers::get;
+        use aws_ini::{ Ini, Properties };
+
+        fn get_test_ini() -> Ini {
+            let mut conf = Ini::new();
+            conf.with_section(Some("first_section".to_string()))
+                .set("role_arn", "arn_1")
+                .set("region", "us-east-1");
+            conf.with_section(Some("second_section".to_string()))
+                .set("role_arn", "arn_1")
+                .set("region", "eu-west-1");
+            conf
+        }
+
+        #[test]
+        fn keep_candidates_matching_all_keys() {
+            let conf = get_test_ini();
+            let mut default_properties = Properties::new();
+            default_properties.insert("role_arn".to_string(), "arn_1".to_string());
+            default_properties.insert("region".to_string(), "eu-west-1".to_string());
+
+            let result = get::narrow_by_matching_keys(conf.iter().collect(), &default_properties, &["role_arn", "region"]);
+
+            super::assert_section_names(result, &["second_section"]);
+        }
+
+        #[test]
+        fn keep_all_candidates_if_none_matches_all_keys() {
+            let conf = get_test_ini();
+            let mut default_properties = Properties::new();
+            default_properties.insert("role_arn".to_string(), "arn_1".to_string());
+
+            let result = get::narrow_by_matching_keys(conf.iter().collect(), &default_properties, &["role_arn", "region"]);
+
+            super::assert_section_names(result, &["first_section", "second_section"]);
         }
     }
 
@@ -155,9 +238,9 @@ mod tests {
         }
     }
 
-    mod find_section_with_same_access_key {
-        use handlers::get::find_section_with_same_access_key;
-        use ini::Ini;
+    mod find_sections_with_same_access_key {
+        use handlers::get::find_sections_with_same_access_key;
+        use aws_ini::Ini;
 
         fn get_test_ini() -> Ini {
             let mut conf = Ini::new();
@@ -169,23 +252,22 @@ mod tests {
         }
 
         #[test]
-        fn return_none_if_not_found() {
+        fn return_empty_if_not_found() {
             let conf = get_test_ini();
-            let result = find_section_with_same_access_key(&conf)(&"access_key_3".to_string());
-            assert!(result.is_none());
+            let result = find_sections_with_same_access_key(&conf)(&"access_key_3".to_string());
+            assert!(result.is_empty());
         }
 
         #[test]
-        fn return_some_if_found() {
+        fn return_sections_if_found() {
             let conf = get_test_ini();
-            let result = find_section_with_same_access_key(&conf)(&"access_key_2".to_string());
-            assert!(result.is_some());
-            super::assert_section_name(result, "second_section");
+            let result = find_sections_with_same_access_key(&conf)(&"access_key_2".to_string());
+            super::assert_section_names(result, &["second_section"]);
         }
     }
 
-    fn assert_section_name(result: Option<(&Option<String>, &Properties)>, expected: &str) {
-        let (section_name, _) = result.unwrap();
-        assert_eq!(section_name.clone().unwrap(), expected.to_string());
+    fn assert_section_names(result: Vec<(&Option<String>, &Properties)>, expected: &[&str]) {
+        let section_names: Vec<String> = result.iter().filter_map(|(section_name, _)| (*section_name).clone()).collect();
+        assert_eq!(section_names, expected);
     }
 }
@@ -0,0 +1,216 @@
+use handlers::common::{ load_ini, to_profile_name };
+use handlers::get::find_current_profile_name;
+use handlers::picker::PickerResult;
+use handlers::set;
+use handlers::source_profile::is_same_profile;
+use aws_ini::Ini;
+use config::{ HistoryConfig, SetConfig };
+use error::RawsError;
+use shellexpand::tilde;
+use std::error::Error;
+use std::fs;
+use std::io::ErrorKind;
+
+const MAX_HISTORY_SIZE: usize = 10;
+
+pub fn load_history(path: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    match fs::read_to_string(tilde(path).to_string()) {
+        // entries recorded by older versions may still have `profile ` prefix
+        Ok(content) => Ok(content.lines()
+                                 .map(|line| to_profile_name(line.trim()))
+                                 .filter(|line| !line.is_empty())
+                                 .collect()),
+        Err(ref e) if e.kind() == ErrorKind::NotFound => Ok(vec!()),
+        Err(e) => Err(RawsError::read_failed(path, &e).into())
+    }
+}
+
+pub fn save_history(path: &str, history: &[String]) -> Result<(), Box<dyn Error>> {
+    let mut content = history.join("\n");
+    content.push('\n');
+    fs::write(tilde(path).to_string(), content)
+        .map_err(|e| RawsError::write_failed(path, e).into())
+}
+
+pub fn add_to_history(history: Vec<String>, profile: &str) -> Vec<String> {
+    let mut updated_history = vec!(profile.to_string());
+    updated_history.extend(history.into_iter()
+                                  .filter(|entry| entry.to_lowercase() != profile.to_lowercase()));
+    updated_history.truncate(MAX_HISTORY_SIZE);
+    updated_history
+}
+
+// the new name may already be in history if only its case changed, so only the first entry is kept
+pub fn rename_in_history(history: Vec<String>, profile: &str, new_profile: &str) -> Vec<String> {
+    let mut updated_history: Vec<String> = vec!();
+    for entry in history {
+        let entry = if entry.to_lowercase() == profile.to_lowercase() { new_profile.to_string() } else { entry };
+        if !updated_history.iter().any(|updated_entry| updated_entry.to_lowercase() == entry.to_lowercase()) {
+            updated_history.push(entry);
+        }
+    }
+    updated_history
+}
+
+pub fn remove_from_history(history: Vec<String>, profiles: &[String]) -> Vec<String> {
+    history.into_iter()
+        .filter(|entry| !profiles.iter().any(|profile| profile.to_lowercase() == entry.to_lowercase()))
+        .collect()
+}
+
+pub fn find_previous_profile(history: &[String], current_profile: &Option<String>) -> Option<String> {
+    history.iter()
+        .find(|entry| match current_profile {
+            Some(current) => entry.to_lowercase() != current.to_lowercase(),
+            None => true
+        })
+        .cloned()
+}
+
+fn format_history(history: &[String], current_profile: &Option<String>) -> String {
+    history.iter()
+        .enumerate()
+        .map(|(index, entry)| {
+            let is_current = current_profile.as_ref().is_some_and(|current| is_same_profile(current, entry));
+            let marker = if is_current { "*" } else { " " };
+            format!("{} {}  {}", marker, index, entry)
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+pub fn handle(config: HistoryConfig,
+              mut choose_profile: impl FnMut(Vec<String>, &Ini, &Ini) -> Result<PickerResult, Box<dyn Error>>,
+              write_to_files: impl FnMut(Vec<(Ini, String)>) -> Result<(), Box<dyn Error>>)
+              -> Result<String, Box<dyn Error>> {
+    let history = load_history(&config.history_path)?;
+    if history.is_empty() {
+        return Err(RawsError::Other(String::from("no profile history recorded yet")).into());
+    }
+
+    let config_file = load_ini(&config.config_path)?;
+    let credentials_file = load_ini(&config.credentials_path)?;
+    if !config.select {
+        let current_profile = find_current_profile_name(&config_file, &credentials_file, &config.copy_keys);
+        return Ok(format_history(&history, &current_profile));
+    }
+
+    let selected_profile = match choose_profile(history, &config_file, &credentials_file)? {
+        PickerResult::Selected(profile) => profile,
+        PickerResult::Cancelled => return Err(RawsError::PickerCancelled.into())
+    };
+
+    let set_config = SetConfig {
+        credentials_path: config.credentials_path,
+        config_path: config.config_path,
+        history_path: config.history_path,
+        copied_keys_path: config.copied_keys_path,
+        copy_keys: config.copy_keys,
+        pattern: selected_profile.clone(),
+        picker: config.picker,
+    };
+    // exact match of a history entry never opens fzf, this only happens if the profile has since been removed
+    set::handle(set_config,
+                |_, _, _| Err(RawsError::ProfileNotFound { profile: selected_profile.clone() }.into()),
+                write_to_files)
+}
+
+#[cfg(test)]
+mod tests {
+    mod add_to_history {
+        use handlers::history;
+
+        #[test]
+        fn add_profile_to_the_front_and_remove_duplicates_ignoring_case() {
+            let history = vec!("a".to_string(), "B".to_string(), "c".to_string());
+
+            let updated_history = history::add_to_history(history, "b");
+
+            assert_eq!(vec!("b", "a", "c"), updated_history);
+        }
+
+        #[test]
+        fn keep_at_most_max_history_size_entries() {
+            let history: Vec<String> = (0..history::MAX_HISTORY_SIZE).map(|i| i.to_string()).collect();
+
+            let updated_history = history::add_to_history(history, "new");
+
+            assert_eq!(history::MAX_HISTORY_SIZE, updated_history.len());
+            assert_eq!("new", updated_history[0]);
+            assert_eq!((history::MAX_HISTORY_SIZE - 2).to_string(), updated_history[history::MAX_HISTORY_SIZE - 1]);
+        }
+    }
+
+    mod rename_in_history {
+        use handlers::history;
+
+        #[test]
+        fn rename_entries_of_profile_ignoring_case() {
+            let history = vec!("a".to_string(), "Old".to_string(), "c".to_string());
+
+            assert_eq!(vec!("a", "new", "c"), history::rename_in_history(history, "old", "new"));
+        }
+
+        #[test]
+        fn keep_first_entry_if_new_name_is_already_in_history() {
+            let history = vec!("Old".to_string(), "a".to_string(), "old".to_string());
+
+            assert_eq!(vec!("OLD", "a"), history::rename_in_history(history, "old", "OLD"));
+        }
+    }
+
+    mod remove_from_history {
+        use handlers::history;
+
+        #[test]
+        fn remove_entries_of_profiles_ignoring_case() {
+            let history = vec!("a".to_string(), "B".to_string(), "c".to_string());
+
+            assert_eq!(vec!("c"), history::remove_from_history(history, &["A".to_string(), "b".to_string()]));
+        }
+    }
+
+    mod find_previous_profile {
+        use handlers::history;
+
+        #[test]
+        fn return_most_recent_profile_that_is_not_current() {
+            let history = vec!("current".to_string(), "previous".to_string());
+
+            let result = history::find_previous_profile(&history, &Some("CURRENT".to_string()));
+
+            assert_eq!(Some("previous".to_string()), result);
+        }
+
+        #[test]
+        fn return_most_recent_profile_if_current_profile_is_unknown() {
+            let history = vec!("last".to_string(), "previous".to_string());
+
+            let result = history::find_previous_profile(&history, &None);
+
+            assert_eq!(Some("last".to_string()), result);
+        }
+
+        #[test]
+        fn return_none_if_history_only_contains_current_profile() {
+            let history = vec!("current".to_string());
+
+            let result = history::find_previous_profile(&history, &Some("current".to_string()));
+
+            assert!(result.is_none());
+        }
+    }
+
+    mod format_history {
+        use handlers::history;
+
+        #[test]
+        fn mark_current_profile_ignoring_case() {
+            let history = vec!("Current".to_string(), "previous".to_string());
+
+            let result = history::format_history(&history, &Some("current".to_string()));
+
+            assert_eq!("* 0  Current\n  1  previous", result);
+        }
+    }
+}
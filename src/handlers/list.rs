@@ -0,0 +1,174 @@
+use handlers::common::find_config_profile;
+use handlers::common::{ load_ini, STATIC_KIND, ASSUME_ROLE_KIND };
+use handlers::get::find_current_profile_names;
+use handlers::common::get_assume_settings;
+use handlers::set::get_all_profile_names;
+use aws_ini::Properties;
+use aws_ini::Ini;
+use config::{ ListConfig, OutputFormat };
+use std::error::Error;
+
+#[derive(Debug, PartialEq)]
+pub struct ProfileSummary {
+    pub name: String,
+    pub kind: &'static str,
+    pub region: Option<String>,
+    pub source_profile: Option<String>,
+    pub role_arn: Option<String>,
+    pub is_current: bool,
+}
+
+fn get_property(properties: Option<&Properties>, key: &str) -> Option<String> {
+    properties.and_then(|p| p.get(key)).cloned()
+}
+
+fn build_summary(name: &str, kind: &'static str, properties: Option<&Properties>, current_profiles: &[String]) -> ProfileSummary {
+    ProfileSummary {
+        name: name.to_string(),
+        kind,
+        region: get_property(properties, "region"),
+        source_profile: get_property(properties, "source_profile"),
+        role_arn: get_property(properties, "role_arn"),
+        is_current: current_profiles.iter().any(|current| current.to_lowercase() == name.to_lowercase()),
+    }
+}
+
+// a profile with role_arn is assumed even if it also has keys in credentials file
+pub fn get_profile_kind(config_file: &Ini, name: &str) -> &'static str {
+    if find_config_profile(config_file, name).and_then(get_assume_settings).is_some() { ASSUME_ROLE_KIND } else { STATIC_KIND }
+}
+
+pub fn get_profile_summaries(config_file: &Ini, credentials_file: &Ini, copy_keys: &[String]) -> Vec<ProfileSummary> {
+    // all candidates are marked if default profile matches more than one profile
+    let current_profiles = find_current_profile_names(config_file, credentials_file, copy_keys);
+
+    get_all_profile_names(config_file, credentials_file).iter().map(|name| {
+        // region of a static profile lives in the matching [profile name] section of config file
+        let properties = find_config_profile(config_file, name);
+        build_summary(name, get_profile_kind(config_file, name), properties, &current_profiles)
+    }).collect()
+}
+
+fn to_columns(summary: &ProfileSummary, empty_value: &str) -> Vec<String> {
+    let or_empty = |value: &Option<String>| value.clone().unwrap_or_else(|| empty_value.to_string());
+    vec!(
+        if summary.is_current { "*".to_string() } else { String::new() },
+        summary.name.clone(),
+        summary.kind.to_string(),
+        or_empty(&summary.region),
+        or_empty(&summary.source_profile),
+        or_empty(&summary.role_arn),
+    )
+}
+
+fn format_table(summaries: &[ProfileSummary]) -> String {
+    let header: Vec<String> = ["", "PROFILE", "KIND", "REGION", "SOURCE_PROFILE", "ROLE_ARN"]
+        .iter().map(|column| column.to_string()).collect();
+    let mut rows = vec!(header);
+    rows.extend(summaries.iter().map(|summary| to_columns(summary, "-")));
+
+    let widths: Vec<usize> = (0..rows[0].len())
+        .map(|index| rows.iter().map(|row| row[index].len()).max().unwrap_or(0))
+        .collect();
+
+    rows.iter()
+        .map(|row| row.iter()
+                      .zip(widths.iter())
+                      .map(|(column, width)| format!("{:width$}", column, width = width))
+                      .collect::<Vec<String>>()
+                      .join("  ")
+                      .trim_end()
+                      .to_string())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn format_plain(summaries: &[ProfileSummary]) -> String {
+    summaries.iter()
+        .map(|summary| to_columns(summary, "").join("\t"))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn format_json(summaries: &[ProfileSummary]) -> Result<String, Box<dyn Error>> {
+    let profiles: Vec<_> = summaries.iter().map(|summary| json!({
+        "name": summary.name,
+        "kind": summary.kind,
+        "region": summary.region,
+        "source_profile": summary.source_profile,
+        "role_arn": summary.role_arn,
+        "current": summary.is_current,
+    })).collect();
+
+    serde_json::to_string_pretty(&profiles).map_err(|e| e.into())
+}
+
+pub fn handle(config: ListConfig) -> Result<String, Box<dyn Error>> {
+    let config_file = load_ini(&config.config_path)?;
+    let credentials_file = load_ini(&config.credentials_path)?;
+
+    let summaries = get_profile_summaries(&config_file, &credentials_file, &config.copy_keys);
+
+    match config.output {
+        OutputFormat::Table => Ok(format_table(&summaries)),
+        OutputFormat::Plain => Ok(format_plain(&summaries)),
+        OutputFormat::Json => format_json(&summaries),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use handlers::list::ProfileSummary;
+
+    fn get_test_summaries() -> Vec<ProfileSummary> {
+        vec!(
+            ProfileSummary {
+                name: "first_profile".to_string(),
+                kind: "static",
+                region: Some("us-east-1".to_string()),
+                source_profile: None,
+                role_arn: None,
+                is_current: false,
+            },
+            ProfileSummary {
+                name: "profile assumed".to_string(),
+                kind: "assume-role",
+                region: None,
+                source_profile: Some("first_profile".to_string()),
+                role_arn: Some("arn".to_string()),
+                is_current: true,
+            },
+        )
+    }
+
+    mod format_table {
+        use handlers::list;
+
+        #[test]
+        fn align_columns_and_mark_current_profile() {
+            let output = list::format_table(&super::get_test_summaries());
+
+            let expected = [
+                "   PROFILE          KIND         REGION     SOURCE_PROFILE  ROLE_ARN",
+                "   first_profile    static       us-east-1  -               -",
+                "*  profile assumed  assume-role  -          first_profile   arn",
+            ].join("\n");
+            assert_eq!(expected, output);
+        }
+    }
+
+    mod format_plain {
+        use handlers::list;
+
+        #[test]
+        fn separate_columns_with_tab_without_header() {
+            let output = list::format_plain(&super::get_test_summaries());
+
+            let expected = [
+                "\tfirst_profile\tstatic\tus-east-1\t\t",
+                "*\tprofile assumed\tassume-role\t\tfirst_profile\tarn",
+            ].join("\n");
+            assert_eq!(expected, output);
+        }
+    }
+}
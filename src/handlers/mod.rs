@@ -2,4 +2,19 @@
 mod common;
 pub mod get;
 pub mod set;
+pub mod copied_keys;
+pub mod picker;
 pub mod fzf;
+pub mod builtin_picker;
+pub mod list;
+pub mod describe;
+pub mod source_profile;
+pub mod validate;
+pub mod add;
+pub mod add_role;
+pub mod remove;
+pub mod rename;
+pub mod prompt;
+pub mod env;
+pub mod history;
+pub mod restore_backup;
@@ -0,0 +1,435 @@
+use aws_ini::Ini;
+use config::{ PickerKind, get_raws_config_path };
+use error::RawsError;
+use handlers::builtin_picker::{ BuiltinPicker, ProfileKinds };
+use handlers::common::load_ini;
+use handlers::fzf;
+use handlers::list::get_profile_kind;
+use handlers::set::get_all_profile_names;
+use std::env;
+use std::error::Error;
+use std::io::{ ErrorKind, Write };
+use std::process::{ Command, Stdio };
+
+pub const PROMPT: &str = "Select AWS profile";
+pub const CUSTOM_PICKER_VARIABLE: &str = "RAWS_PICKER";
+// placeholder in arguments of custom picker command that is replaced with initial query
+pub const QUERY_PLACEHOLDER: &str = "{query}";
+
+pub struct PickerOptions {
+    pub kind: PickerKind,
+    pub config_path: String,
+    pub credentials_path: String,
+}
+
+// profile details that pickers may show next to profile names
+#[derive(Default)]
+struct PickerContext {
+    preview_command: Option<String>,
+    kinds: ProfileKinds,
+}
+
+// a picker that fails to start or crashes returns an error instead
+#[derive(Debug, PartialEq, Clone)]
+pub enum PickerResult {
+    Selected(String),
+    // cancelled by user or no profile matches query
+    Cancelled,
+}
+
+pub trait Picker {
+    fn choose(&self, profiles: Vec<String>, query: &str) -> Result<PickerResult, Box<dyn Error>>;
+}
+
+// picker running an external selector that reads profiles from stdin and prints the selected one to stdout
+pub struct CommandPicker {
+    pub program: String,
+    pub args: Vec<String>,
+    // exit codes of the command when it's cancelled or nothing matches, any other non-zero exit code is a failure
+    pub cancelled_exit_codes: Vec<i32>,
+}
+
+impl CommandPicker {
+    pub fn new(program: &str, args: &[&str], cancelled_exit_codes: &[i32]) -> CommandPicker {
+        CommandPicker {
+            program: program.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            cancelled_exit_codes: cancelled_exit_codes.to_vec(),
+        }
+    }
+}
+
+impl Picker for CommandPicker {
+    fn choose(&self, profiles: Vec<String>, query: &str) -> Result<PickerResult, Box<dyn Error>> {
+        let args: Vec<String> = self.args.iter().map(|arg| arg.replace(QUERY_PLACEHOLDER, query)).collect();
+        let mut child = Command::new(&self.program)
+            .args(&args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| RawsError::PickerUnavailable { picker: self.program.clone(), reason: e.to_string() })?;
+
+        let stdin = child.stdin.as_mut().ok_or_else(|| format!("failed to access {} stdin", self.program))?;
+        // picker may exit without reading all profiles, e.g. when it's cancelled right away
+        match stdin.write_all(profiles.join("\n").as_bytes()) {
+            Err(ref e) if e.kind() == ErrorKind::BrokenPipe => (),
+            result => result?
+        }
+
+        let output = child.wait_with_output()?;
+        let selected_profile = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        match output.status.code() {
+            Some(0) if selected_profile.is_empty() => Ok(PickerResult::Cancelled),
+            Some(0) => Ok(PickerResult::Selected(selected_profile)),
+            Some(code) if self.cancelled_exit_codes.contains(&code) => Ok(PickerResult::Cancelled),
+            _ => Err(RawsError::PickerFailed { picker: self.program.clone(), reason: format!("exited with {}", output.status) }.into())
+        }
+    }
+}
+
+// uses fallback picker if primary one is not installed
+pub struct FallbackPicker {
+    primary: Box<dyn Picker>,
+    fallback: Box<dyn Picker>,
+}
+
+impl Picker for FallbackPicker {
+    fn choose(&self, profiles: Vec<String>, query: &str) -> Result<PickerResult, Box<dyn Error>> {
+        match self.primary.choose(profiles.clone(), query) {
+            Err(ref error) if matches!(error.downcast_ref::<RawsError>(), Some(RawsError::PickerUnavailable { .. })) =>
+                self.fallback.choose(profiles, query),
+            result => result
+        }
+    }
+}
+
+// skim accepts the same options as fzf
+fn skim() -> CommandPicker {
+    CommandPicker::new("sk", &["--height", "30%", "--reverse", "-1", "-0", "--header", PROMPT, "--query", QUERY_PLACEHOLDER], &[1, 130])
+}
+
+// peco, rofi and dmenu exit with 1 when they are cancelled
+fn peco() -> CommandPicker {
+    CommandPicker::new("peco", &["--select-1", "--prompt", &format!("{}>", PROMPT), "--query", QUERY_PLACEHOLDER], &[1, 130])
+}
+
+fn rofi() -> CommandPicker {
+    CommandPicker::new("rofi", &["-dmenu", "-i", "-p", PROMPT, "-filter", QUERY_PLACEHOLDER], &[1])
+}
+
+// dmenu has no option for initial query
+fn dmenu() -> CommandPicker {
+    CommandPicker::new("dmenu", &["-i", "-p", PROMPT], &[1])
+}
+
+// single quotes everything for the shell that runs preview command
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+// command showing details of the highlighted profile, fzf replaces {} with it
+fn get_preview_command(options: &PickerOptions) -> Option<String> {
+    env::current_exe().ok().map(|executable| format!("{} describe --config-path {} --credentials-path {} {{}}",
+                                                      quote(&executable.to_string_lossy()),
+                                                      quote(&options.config_path),
+                                                      quote(&options.credentials_path)))
+}
+
+fn get_profile_kinds(config_file: &Ini, credentials_file: &Ini) -> ProfileKinds {
+    get_all_profile_names(config_file, credentials_file).into_iter().map(|name| {
+        let kind = get_profile_kind(config_file, &name);
+        (name, kind)
+    }).collect()
+}
+
+fn builtin(context: &PickerContext) -> BuiltinPicker {
+    BuiltinPicker { kinds: context.kinds.clone() }
+}
+
+fn to_picker(kind: &PickerKind, context: &PickerContext) -> Option<Box<dyn Picker>> {
+    match kind {
+        PickerKind::Auto => Some(Box::new(FallbackPicker { primary: Box::new(fzf::picker(&context.preview_command)), fallback: Box::new(builtin(context)) })),
+        PickerKind::Fzf => Some(Box::new(fzf::picker(&context.preview_command))),
+        PickerKind::Skim => Some(Box::new(skim())),
+        PickerKind::Peco => Some(Box::new(peco())),
+        PickerKind::Rofi => Some(Box::new(rofi())),
+        PickerKind::Dmenu => Some(Box::new(dmenu())),
+        PickerKind::Builtin => Some(Box::new(builtin(context))),
+        PickerKind::Custom => None
+    }
+}
+
+// splits a command line into words with the quoting rules of POSIX shells, e.g. `fzf --prompt 'aws> '`
+// has 3 words, returns None if a quote is not closed
+fn split_shell_words(command: &str) -> Option<Vec<String>> {
+    let mut words = vec!();
+    // a word is started by a quote too, so that '' is an empty word
+    let mut word: Option<String> = None;
+    let mut chars = command.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next()? {
+                        '\'' => break,
+                        c => word.push(c)
+                    }
+                }
+            },
+            '"' => {
+                let word = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next()? {
+                        '"' => break,
+                        '\\' => match chars.next()? {
+                            c @ ('"' | '\\' | '$' | '`') => word.push(c),
+                            c => { word.push('\\'); word.push(c); }
+                        },
+                        c => word.push(c)
+                    }
+                }
+            },
+            '\\' => word.get_or_insert_with(String::new).push(chars.next()?),
+            c if c.is_whitespace() => words.extend(word.take()),
+            c => word.get_or_insert_with(String::new).push(c)
+        }
+    }
+    words.extend(word);
+    Some(words)
+}
+
+// custom picker command is either the name of a supported picker, e.g. `sk`,
+// or a command line with optional {query} placeholder, e.g. `fzf-tmux -p --query {query}`
+fn parse_custom_picker(command: &str, context: &PickerContext) -> Result<Option<Box<dyn Picker>>, RawsError> {
+    let words = split_shell_words(command).ok_or_else(|| RawsError::PickerUnavailable {
+        picker: "custom".to_string(),
+        reason: format!("unclosed quote in command {}", command),
+    })?;
+    let (program, args) = match words.split_first() {
+        Some((program, args)) => (program, args.iter().map(|arg| arg.as_str()).collect::<Vec<&str>>()),
+        None => return Ok(None)
+    };
+
+    match (PickerKind::from_name(program), args.is_empty()) {
+        (Some(ref kind), true) if *kind != PickerKind::Custom => Ok(to_picker(kind, context)),
+        _ => Ok(Some(Box::new(CommandPicker::new(program, &args, &[1, 130]))))
+    }
+}
+
+// RAWS_PICKER takes precedence over `command` in [picker] section of raws config file
+fn find_custom_command(variable: Option<String>, raws_config: &Ini) -> Option<String> {
+    variable.filter(|value| !value.trim().is_empty())
+            .or_else(|| raws_config.get_from(Some("picker"), "command").map(|value| value.to_string()))
+}
+
+fn create_picker(kind: &PickerKind, custom_command: Option<String>, context: &PickerContext) -> Result<Box<dyn Picker>, RawsError> {
+    let custom_picker = match custom_command {
+        Some(ref command) => parse_custom_picker(command, context)?,
+        None => None
+    };
+    match (kind, custom_picker) {
+        (PickerKind::Auto, Some(picker)) | (PickerKind::Custom, Some(picker)) => Ok(picker),
+        (kind, _) => to_picker(kind, context).ok_or_else(|| RawsError::PickerUnavailable {
+            picker: "custom".to_string(),
+            reason: format!("no command set in {} or [picker] section of raws config file", CUSTOM_PICKER_VARIABLE),
+        })
+    }
+}
+
+// config and credentials files are the ones the caller already loaded, the picker shows the kind of each profile
+pub fn choose_profile(options: &PickerOptions,
+                      config_file: &Ini,
+                      credentials_file: &Ini,
+                      profiles: Vec<String>,
+                      query: &str)
+                      -> Result<PickerResult, Box<dyn Error>> {
+    let raws_config = load_ini(&get_raws_config_path(&options.config_path))?;
+    let custom_command = find_custom_command(env::var(CUSTOM_PICKER_VARIABLE).ok(), &raws_config);
+    let context = PickerContext {
+        preview_command: get_preview_command(options),
+        kinds: get_profile_kinds(config_file, credentials_file),
+    };
+    create_picker(&options.kind, custom_command, &context)?.choose(profiles, query)
+}
+
+#[cfg(test)]
+mod tests {
+    fn get_profiles() -> Vec<String> {
+        vec!("first_profile".to_string(), "second_profile".to_string())
+    }
+
+    mod command_picker {
+        use error::RawsError;
+        use handlers::picker::{ CommandPicker, Picker, PickerResult };
+
+        #[test]
+        fn return_profile_printed_by_command() {
+            let picker = CommandPicker::new("tail", &["-n", "1"], &[1]);
+
+            let result = picker.choose(super::get_profiles(), "");
+
+            assert_eq!(PickerResult::Selected("second_profile".to_string()), result.unwrap());
+        }
+
+        #[test]
+        fn replace_query_placeholder_in_args() {
+            let picker = CommandPicker::new("grep", &["{query}"], &[1]);
+
+            let result = picker.choose(super::get_profiles(), "first");
+
+            assert_eq!(PickerResult::Selected("first_profile".to_string()), result.unwrap());
+        }
+
+        #[test]
+        fn return_cancelled_for_cancelled_exit_code() {
+            let picker = CommandPicker::new("grep", &["{query}"], &[1]);
+
+            let result = picker.choose(super::get_profiles(), "third");
+
+            assert_eq!(PickerResult::Cancelled, result.unwrap());
+        }
+
+        #[test]
+        fn return_cancelled_if_command_prints_nothing() {
+            let picker = CommandPicker::new("true", &[], &[]);
+
+            let result = picker.choose(super::get_profiles(), "");
+
+            assert_eq!(PickerResult::Cancelled, result.unwrap());
+        }
+
+        #[test]
+        fn return_failed_for_other_exit_codes() {
+            let picker = CommandPicker::new("false", &[], &[130]);
+
+            let result = picker.choose(super::get_profiles(), "");
+
+            let error = result.unwrap_err();
+            assert!(matches!(error.downcast_ref::<RawsError>(), Some(RawsError::PickerFailed { .. })));
+        }
+
+        #[test]
+        fn return_unavailable_if_command_is_not_installed() {
+            let picker = CommandPicker::new("raws_not_existing_picker", &[], &[]);
+
+            let result = picker.choose(super::get_profiles(), "");
+
+            let error = result.unwrap_err();
+            assert!(matches!(error.downcast_ref::<RawsError>(), Some(RawsError::PickerUnavailable { .. })));
+        }
+    }
+
+    mod fallback_picker {
+        use handlers::picker::{ CommandPicker, FallbackPicker, Picker, PickerResult };
+
+        #[test]
+        fn use_fallback_picker_if_primary_picker_is_unavailable() {
+            let picker = FallbackPicker {
+                primary: Box::new(CommandPicker::new("raws_not_existing_picker", &[], &[])),
+                fallback: Box::new(CommandPicker::new("head", &["-n", "1"], &[])),
+            };
+
+            let result = picker.choose(super::get_profiles(), "");
+
+            assert_eq!(PickerResult::Selected("first_profile".to_string()), result.unwrap());
+        }
+    }
+
+    mod find_custom_command {
+        use aws_ini::Ini;
+        use handlers::picker::find_custom_command;
+
+        fn get_raws_config() -> Ini {
+            let mut raws_config = Ini::new();
+            raws_config.with_section(Some("picker")).set("command", "fzf-tmux -p");
+            raws_config
+        }
+
+        #[test]
+        fn prefer_environment_variable_over_raws_config() {
+            let result = find_custom_command(Some("peco".to_string()), &get_raws_config());
+
+            assert_eq!(Some("peco".to_string()), result);
+        }
+
+        #[test]
+        fn use_raws_config_if_environment_variable_is_empty() {
+            let result = find_custom_command(Some(" ".to_string()), &get_raws_config());
+
+            assert_eq!(Some("fzf-tmux -p".to_string()), result);
+        }
+    }
+
+    mod quote {
+        use handlers::picker::quote;
+
+        #[test]
+        fn escape_single_quotes() {
+            assert_eq!("'/home/some'\\''one/.aws/config'", quote("/home/some'one/.aws/config"));
+        }
+    }
+
+    mod split_shell_words {
+        use handlers::picker::split_shell_words;
+
+        #[test]
+        fn keep_quoted_words_with_spaces_together() {
+            let words = split_shell_words("fzf --prompt 'aws> ' --header \"a \\\"b\\\"\" c\\ d ''").unwrap();
+
+            assert_eq!(vec!("fzf", "--prompt", "aws> ", "--header", "a \"b\"", "c d", ""), words);
+        }
+
+        #[test]
+        fn return_none_if_quote_is_not_closed() {
+            assert!(split_shell_words("fzf --prompt \"aws> ").is_none());
+        }
+    }
+
+    mod create_picker {
+        use config::PickerKind;
+        use error::RawsError;
+        use handlers::picker::{ create_picker, PickerContext, PickerResult };
+
+        #[test]
+        fn use_custom_command_by_default_if_set() {
+            let picker = create_picker(&PickerKind::Auto, Some("head -n 1".to_string()), &PickerContext::default()).unwrap();
+
+            let result = picker.choose(super::get_profiles(), "");
+
+            assert_eq!(PickerResult::Selected("first_profile".to_string()), result.unwrap());
+        }
+
+        #[test]
+        fn ignore_custom_command_if_picker_is_given_explicitly() {
+            let picker = create_picker(&PickerKind::Builtin, Some("head -n 1".to_string()), &PickerContext::default()).unwrap();
+
+            let result = picker.choose(super::get_profiles(), "second");
+
+            assert_eq!(PickerResult::Selected("second_profile".to_string()), result.unwrap());
+        }
+
+        #[test]
+        fn keep_quoted_arguments_of_custom_command_together() {
+            let picker = create_picker(&PickerKind::Custom, Some("sh -c 'tail -n 1'".to_string()), &PickerContext::default()).unwrap();
+
+            let result = picker.choose(super::get_profiles(), "");
+
+            assert_eq!(PickerResult::Selected("second_profile".to_string()), result.unwrap());
+        }
+
+        #[test]
+        fn return_unavailable_if_quote_of_custom_command_is_not_closed() {
+            let result = create_picker(&PickerKind::Custom, Some("fzf --prompt 'aws> ".to_string()), &PickerContext::default());
+
+            assert!(matches!(result.err(), Some(RawsError::PickerUnavailable { .. })));
+        }
+
+        #[test]
+        fn return_unavailable_if_custom_picker_has_no_command() {
+            let result = create_picker(&PickerKind::Custom, None, &PickerContext::default());
+
+            assert!(matches!(result.err(), Some(RawsError::PickerUnavailable { .. })));
+        }
+    }
+}
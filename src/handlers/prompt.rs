@@ -0,0 +1,102 @@
+use error::RawsError;
+use std::error::Error;
+use std::io::{ self, BufRead, Write };
+
+#[cfg(unix)]
+mod terminal {
+    use libc;
+    use std::io::{ self, Read };
+    use std::mem;
+
+    const CTRL_C: u8 = 3;
+    const CTRL_D: u8 = 4;
+    const BACKSPACE: u8 = 8;
+    const DELETE: u8 = 127;
+
+    // turns off echo and line buffering of the terminal until dropped, e.g. while a secret is typed,
+    // signals are turned off too so that Ctrl-C is read as a key and never leaves the terminal without echo
+    pub struct HiddenInput {
+        original: libc::termios,
+    }
+
+    impl HiddenInput {
+        pub fn start() -> io::Result<HiddenInput> {
+            if unsafe { libc::isatty(libc::STDIN_FILENO) } != 1 {
+                return Err(io::Error::from(io::ErrorKind::NotFound));
+            }
+            let mut original: libc::termios = unsafe { mem::zeroed() };
+            if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut original) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            let mut hidden = original;
+            hidden.c_lflag &= !(libc::ECHO | libc::ICANON | libc::ISIG);
+            hidden.c_cc[libc::VMIN] = 1;
+            hidden.c_cc[libc::VTIME] = 0;
+            if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &hidden) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(HiddenInput { original })
+        }
+
+        // returns None if Ctrl-C is pressed
+        pub fn read_line(&self) -> io::Result<Option<String>> {
+            let mut input: Vec<u8> = vec!();
+            let stdin = io::stdin();
+            for byte in stdin.lock().bytes() {
+                match byte? {
+                    b'\r' | b'\n' => break,
+                    CTRL_C => {
+                        eprintln!();
+                        return Ok(None);
+                    },
+                    CTRL_D if input.is_empty() => break,
+                    BACKSPACE | DELETE => {
+                        // removes the whole last character, including the continuation bytes of UTF-8
+                        while let Some(last) = input.pop() {
+                            if last & 0b1100_0000 != 0b1000_0000 {
+                                break;
+                            }
+                        }
+                    },
+                    byte => input.push(byte)
+                }
+            }
+            eprintln!();
+            Ok(Some(String::from_utf8_lossy(&input).to_string()))
+        }
+    }
+
+    impl Drop for HiddenInput {
+        fn drop(&mut self) {
+            unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.original) };
+        }
+    }
+}
+
+fn read_line() -> Result<String, Box<dyn Error>> {
+    let mut line = String::new();
+    io::stdin().lock().read_line(&mut line)?;
+    Ok(line.trim().to_string())
+}
+
+// prompt goes to stderr so that it's shown even if stdout is captured,
+// hidden input is only supported on unix and is read with echo on elsewhere
+pub fn read_input(message: &str, hidden: bool) -> Result<String, Box<dyn Error>> {
+    eprint!("{}: ", message);
+    io::stderr().flush()?;
+
+    #[cfg(unix)]
+    {
+        if hidden {
+            // input is piped if there's no terminal, nothing to hide then
+            if let Ok(hidden_input) = terminal::HiddenInput::start() {
+                return match hidden_input.read_line()? {
+                    Some(line) => Ok(line.trim().to_string()),
+                    None => Err(RawsError::InputCancelled.into())
+                };
+            }
+        }
+    }
+    read_line()
+}
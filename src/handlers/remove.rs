@@ -0,0 +1,127 @@
+use handlers::common::{ find_profile_with_name, find_config_profile, get_assume_settings, load_ini };
+use handlers::common::{ to_config_section_name, to_profile_name };
+use handlers::get::find_current_profile_names;
+use handlers::history::{ load_history, save_history, remove_from_history };
+use handlers::set::remove_default_assume_settings;
+use handlers::copied_keys::revert_copied_keys;
+use handlers::source_profile::{ find_referencing_profiles, is_same_profile, profile_exists };
+use aws_ini::Ini;
+use config::RemoveConfig;
+use error::RawsError;
+use std::error::Error;
+
+// the profile followed by every profile that uses it as source profile directly or through other profiles,
+// default profile is not included since it's not removed
+fn find_profiles_to_remove(config_file: &Ini, profile: &str) -> Vec<String> {
+    let mut profiles = vec!(profile.to_string());
+    let mut index = 0;
+    while index < profiles.len() {
+        for referencing_profile in find_referencing_profiles(config_file, &profiles[index]) {
+            if !is_same_profile(&referencing_profile, "default") && !profiles.iter().any(|profile| is_same_profile(profile, &referencing_profile)) {
+                profiles.push(referencing_profile);
+            }
+        }
+        index += 1;
+    }
+    profiles
+}
+
+// default profile uses a removed profile if it's set to one of them or its source profile is removed,
+// AWS CLI would fail to assume its role afterwards or it would point at a role no profile has anymore
+fn default_uses_removed_profiles(config_file: &Ini, credentials_file: &Ini, removed_profiles: &[String], copy_keys: &[String]) -> bool {
+    match find_config_profile(config_file, "default").and_then(get_assume_settings) {
+        Some((_, source_profile)) => {
+            let current_profiles = find_current_profile_names(config_file, credentials_file, copy_keys);
+            let is_removed = |name: &str| removed_profiles.iter().any(|profile| is_same_profile(profile, name));
+            is_removed(&to_profile_name(source_profile)) ||
+                (!current_profiles.is_empty() && current_profiles.iter().all(|profile| is_removed(profile)))
+        },
+        None => false
+    }
+}
+
+fn delete_profile_section(file: &mut Ini, section_name: &str) {
+    let existing_section_name = find_profile_with_name(file, section_name).and_then(|(section, _)| section.clone());
+    if let Some(existing_section_name) = existing_section_name {
+        file.delete_section(existing_section_name);
+    }
+}
+
+fn format_profiles(profiles: &[String]) -> String {
+    profiles.iter().map(|profile| format!("[{}]", profile)).collect::<Vec<String>>().join(", ")
+}
+
+pub fn handle(config: RemoveConfig,
+              mut write_to_files: impl FnMut(Vec<(Ini, String)>) -> Result<(), Box<dyn Error>>)
+              -> Result<String, Box<dyn Error>> {
+    let config_file = load_ini(&config.config_path)?;
+    let credentials_file = load_ini(&config.credentials_path)?;
+
+    let profile = to_profile_name(&config.profile);
+    if is_same_profile(&profile, "default") {
+        return Err(RawsError::invalid_value("profile name", "default profile can't be removed").into());
+    }
+    if !profile_exists(&config_file, &credentials_file, &profile) {
+        return Err(RawsError::ProfileNotFound { profile }.into());
+    }
+
+    let removed_profiles = find_profiles_to_remove(&config_file, &profile);
+    if removed_profiles.len() > 1 && !config.cascade {
+        return Err(RawsError::ProfileReferenced { profile, profiles: removed_profiles[1..].to_vec() }.into());
+    }
+
+    let mut updated_config_file = config_file.clone();
+    let mut updated_credentials_file = credentials_file.clone();
+    for removed_profile in &removed_profiles {
+        delete_profile_section(&mut updated_config_file, &to_config_section_name(removed_profile));
+        delete_profile_section(&mut updated_credentials_file, removed_profile);
+    }
+
+    let mut messages = vec!(match removed_profiles.len() {
+        1 => format!("aws profile [{}] is removed", profile),
+        _ => format!("aws profiles {} are removed", format_profiles(&removed_profiles))
+    });
+    let mut cleared_copied_keys_file = None;
+    if default_uses_removed_profiles(&config_file, &credentials_file, &removed_profiles, &config.copy_keys) {
+        let copied_keys_file = load_ini(&config.copied_keys_path)?;
+        updated_config_file = remove_default_assume_settings(&revert_copied_keys(&updated_config_file, &copied_keys_file));
+        if !copied_keys_file.to_string().is_empty() {
+            cleared_copied_keys_file = Some((Ini::new(), config.copied_keys_path.clone()));
+        }
+        messages.push("role settings are removed from default profile since it used a removed profile".to_string());
+    }
+
+    let mut files = vec!(
+        (updated_config_file, config.config_path.clone()),
+        (updated_credentials_file, config.credentials_path.clone()),
+    );
+    files.extend(cleared_copied_keys_file);
+    write_to_files(files)?;
+
+    // otherwise `raws set -` would select a profile that no longer exists
+    let history = load_history(&config.history_path)?;
+    let updated_history = remove_from_history(history.clone(), &removed_profiles);
+    if updated_history != history {
+        save_history(&config.history_path, &updated_history)?;
+    }
+    Ok(messages.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    mod find_profiles_to_remove {
+        use aws_ini::Ini;
+        use handlers::remove::find_profiles_to_remove;
+
+        #[test]
+        fn include_profiles_using_profile_through_other_profiles() {
+            let mut config_file = Ini::new();
+            config_file.with_section(Some("default")).set("role_arn", "1").set("source_profile", "static");
+            config_file.with_section(Some("profile first")).set("role_arn", "1").set("source_profile", "static");
+            config_file.with_section(Some("profile second")).set("role_arn", "2").set("source_profile", "First");
+            config_file.with_section(Some("profile other")).set("role_arn", "3").set("source_profile", "other_static");
+
+            assert_eq!(vec!("static", "first", "second"), find_profiles_to_remove(&config_file, "static"));
+        }
+    }
+}
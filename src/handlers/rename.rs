@@ -0,0 +1,84 @@
+use handlers::common::{ find_profile_with_name, find_config_profile, get_config_section_name, load_ini };
+use handlers::common::{ to_config_section_name, to_profile_name };
+use handlers::add::validate_profile_name;
+use handlers::history::{ load_history, save_history, rename_in_history };
+use handlers::source_profile::{ find_referencing_profiles, is_same_profile, profile_exists };
+use aws_ini::Ini;
+use config::RenameConfig;
+use error::RawsError;
+use std::error::Error;
+
+// returns false if the file has no section for the profile
+fn rename_profile_section(file: &mut Ini, section_name: &str, new_section_name: &str) -> bool {
+    let existing_section_name = find_profile_with_name(file, section_name).and_then(|(section, _)| section.clone());
+    match existing_section_name {
+        Some(existing_section_name) => file.rename_section(existing_section_name, new_section_name.to_string()),
+        None => false
+    }
+}
+
+fn is_own_source_profile(config_file: &Ini, profile: &str) -> bool {
+    find_config_profile(config_file, profile)
+        .and_then(|properties| properties.get("source_profile"))
+        .is_some_and(|source_profile| is_same_profile(&to_profile_name(source_profile), profile))
+}
+
+// source_profile of other profiles including default profile is updated so that none of them is left
+// pointing at the old name, same for a profile that uses its own static credentials
+fn rename_in_config_file(config_file: &Ini, profile: &str, new_profile: &str) -> (Ini, Vec<String>) {
+    let mut output = config_file.clone();
+    let referencing_profiles = find_referencing_profiles(config_file, profile);
+    for referencing_profile in &referencing_profiles {
+        let section_name = get_config_section_name(config_file, referencing_profile);
+        output.set_to(Some(section_name), "source_profile".to_string(), new_profile.to_string());
+    }
+
+    if rename_profile_section(&mut output, &to_config_section_name(profile), &to_config_section_name(new_profile)) &&
+       is_own_source_profile(config_file, profile) {
+        output.set_to(Some(to_config_section_name(new_profile)), "source_profile".to_string(), new_profile.to_string());
+    }
+    (output, referencing_profiles)
+}
+
+pub fn handle(config: RenameConfig,
+              mut write_to_files: impl FnMut(Vec<(Ini, String)>) -> Result<(), Box<dyn Error>>)
+              -> Result<String, Box<dyn Error>> {
+    let config_file = load_ini(&config.config_path)?;
+    let credentials_file = load_ini(&config.credentials_path)?;
+
+    let profile = to_profile_name(&config.profile);
+    let new_profile = to_profile_name(&config.new_profile);
+    if is_same_profile(&profile, "default") {
+        return Err(RawsError::invalid_value("profile name", "default profile can't be renamed").into());
+    }
+    if !profile_exists(&config_file, &credentials_file, &profile) {
+        return Err(RawsError::ProfileNotFound { profile }.into());
+    }
+    validate_profile_name(&new_profile)?;
+    // only the case of the name may change if the new name matches the profile itself
+    if !is_same_profile(&profile, &new_profile) && profile_exists(&config_file, &credentials_file, &new_profile) {
+        return Err(RawsError::invalid_value("new profile name", &format!("profile [{}] already exists", new_profile)).into());
+    }
+
+    let (updated_config_file, referencing_profiles) = rename_in_config_file(&config_file, &profile, &new_profile);
+    let mut updated_credentials_file = credentials_file.clone();
+    rename_profile_section(&mut updated_credentials_file, &profile, &new_profile);
+
+    write_to_files(vec!(
+        (updated_config_file, config.config_path.clone()),
+        (updated_credentials_file, config.credentials_path.clone()),
+    ))?;
+
+    let history = load_history(&config.history_path)?;
+    let updated_history = rename_in_history(history.clone(), &profile, &new_profile);
+    if updated_history != history {
+        save_history(&config.history_path, &updated_history)?;
+    }
+
+    let mut messages = vec!(format!("aws profile [{}] is renamed to [{}]", profile, new_profile));
+    if !referencing_profiles.is_empty() {
+        let profiles: Vec<String> = referencing_profiles.iter().map(|profile| format!("[{}]", profile)).collect();
+        messages.push(format!("source_profile of {} is updated", profiles.join(", ")));
+    }
+    Ok(messages.join("\n"))
+}
@@ -0,0 +1,87 @@
+use handlers::common::load_ini;
+use handlers::history::{ load_history, save_history, remove_from_history };
+use handlers::source_profile::profile_exists;
+use config::RestoreBackupConfig;
+use error::RawsError;
+use file_transaction::{ find_backup_timestamps, read_backup, restore_files };
+use std::error::Error;
+
+// newest first, config and credentials files written together share the same backup timestamp
+fn find_all_timestamps(paths: &[&String]) -> Vec<u64> {
+    let mut timestamps: Vec<u64> = paths.iter().flat_map(|path| find_backup_timestamps(path)).collect();
+    timestamps.sort_unstable_by(|a, b| b.cmp(a));
+    timestamps.dedup();
+    timestamps
+}
+
+fn format_backups(paths: &[&String], timestamps: &[u64]) -> String {
+    timestamps.iter()
+        .map(|timestamp| {
+            let backed_up_paths: Vec<&str> = paths.iter()
+                .filter(|path| find_backup_timestamps(path).contains(timestamp))
+                .map(|path| path.as_str())
+                .collect();
+            format!("{}  {}", timestamp, backed_up_paths.join(", "))
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn select_timestamp(timestamp: &Option<String>, timestamps: &[u64]) -> Result<u64, RawsError> {
+    match timestamp {
+        Some(timestamp) => {
+            let timestamp: u64 = timestamp.parse()
+                .map_err(|_| RawsError::invalid_value("timestamp", "must be a number as shown by --list"))?;
+            if timestamps.contains(&timestamp) {
+                Ok(timestamp)
+            } else {
+                Err(RawsError::invalid_value("timestamp", &format!("no backup found with timestamp {}", timestamp)))
+            }
+        },
+        None => Ok(timestamps[0])
+    }
+}
+
+// profiles that don't exist in restored files are dropped from history so that `raws set -` doesn't select them
+fn remove_missing_profiles_from_history(config: &RestoreBackupConfig) -> Result<(), Box<dyn Error>> {
+    let history = load_history(&config.history_path)?;
+    if history.is_empty() {
+        return Ok(());
+    }
+    let config_file = load_ini(&config.config_path)?;
+    let credentials_file = load_ini(&config.credentials_path)?;
+    let missing_profiles: Vec<String> = history.iter()
+        .filter(|profile| !profile_exists(&config_file, &credentials_file, profile))
+        .cloned()
+        .collect();
+    if !missing_profiles.is_empty() {
+        save_history(&config.history_path, &remove_from_history(history, &missing_profiles))?;
+    }
+    Ok(())
+}
+
+pub fn handle(config: RestoreBackupConfig) -> Result<String, Box<dyn Error>> {
+    let paths = [&config.config_path, &config.credentials_path];
+    let timestamps = find_all_timestamps(&paths);
+    if timestamps.is_empty() {
+        return Err(RawsError::Other(String::from("no backup found")).into());
+    }
+    if config.list {
+        return Ok(format_backups(&paths, &timestamps));
+    }
+
+    let timestamp = select_timestamp(&config.timestamp, &timestamps)?;
+    let mut files = vec!();
+    for path in paths.iter().filter(|path| find_backup_timestamps(path).contains(&timestamp)) {
+        files.push((read_backup(path, timestamp)?, path.to_string()));
+    }
+
+    let restored_paths: Vec<String> = files.iter().map(|(_, path)| path.clone()).collect();
+    restore_files(files)?;
+
+    let mut messages = vec!(format!("restored backup {} of {}", timestamp, restored_paths.join(", ")));
+    if let Err(e) = remove_missing_profiles_from_history(&config) {
+        messages.push(format!("warning: profile history is not updated: {}", e));
+    }
+    Ok(messages.join("\n"))
+}
@@ -1,20 +1,42 @@
-use handlers::common::find_profile_with_name;
-use handlers::common::get_value_of_tuple;
+use handlers::common::{ find_config_profile, find_credentials_profile, to_profile_name };
 use handlers::common::get_assume_settings;
-use handlers::common::compose;
 use handlers::common::load_ini;
-use ini::ini::Properties;
-use ini::Ini;
+use handlers::get::find_current_profile_name;
+use handlers::source_profile::resolve_source_profiles;
+use handlers::picker::PickerResult;
+use handlers::copied_keys::{ copy_keys, revert_copied_keys };
+use handlers::history::{ load_history, save_history, add_to_history, find_previous_profile };
+use aws_ini::Properties;
+use aws_ini::Ini;
 use config::{ SetConfig };
+use error::RawsError;
 use std::error::Error;
 
-fn get_all_profile_names_except_default(file: &Ini, required_keys: Vec<String>) -> Vec<String> {
+const PREVIOUS_PROFILE_PATTERN: &str = "-";
+// temporary credentials are only valid together with the keys they were issued with,
+// e.g. the token and its expiry written by saml2aws or gimme-aws-creds
+pub const SESSION_KEYS: [&str; 3] = [
+    "aws_session_token",
+    "aws_security_token",
+    "x_security_token_expires",
+];
+pub const ASSUME_ROLE_KEYS: [&str; 7] = [
+    "role_arn",
+    "source_profile",
+    "credential_source",
+    "mfa_serial",
+    "external_id",
+    "duration_seconds",
+    "role_session_name",
+];
+
+pub fn get_all_profile_names_except_default(file: &Ini, required_keys: Vec<String>) -> Vec<String> {
     let mut profiles: Vec<String> = file.iter()
         .filter_map(|(section, properties)|
             match section {
                 Some(section_name)
-                    if (|| section_name != "default" && required_keys.iter().all(|key| properties.contains_key(key)))()
-                  => Some(section_name.clone()),
+                    if to_profile_name(section_name) != "default" && required_keys.iter().all(|key| properties.contains_key(key))
+                  => Some(to_profile_name(section_name)),
                 _ => None
             })
         .collect();
@@ -22,34 +44,81 @@ fn get_all_profile_names_except_default(file: &Ini, required_keys: Vec<String>)
     profiles
 }
 
-fn set_default_assume_settings(file: &Ini, (role_arn, source_profile): (&String, &String)) -> Ini {
-    let mut output = file.clone();
-    output.set_to(Some("default"), "role_arn".to_string(), role_arn.to_string());
-    output.set_to(Some("default"), "source_profile".to_string(), source_profile.to_string());
-    output
+// static profiles come first, a profile with keys in credentials file and role in config file is listed once
+pub fn get_all_profile_names(config_file: &Ini, credentials_file: &Ini) -> Vec<String> {
+    let mut profiles = get_all_profile_names_except_default(credentials_file,
+                                                            vec!("aws_access_key_id".to_string(), "aws_secret_access_key".to_string()));
+    for profile in get_all_profile_names_except_default(config_file, vec!("role_arn".to_string(), "source_profile".to_string())) {
+        if !profiles.iter().any(|existing| existing.to_lowercase() == profile.to_lowercase()) {
+            profiles.push(profile);
+        }
+    }
+    profiles
 }
 
-fn set_assume_profile(config_file: &Ini, credentials_file: &Ini, selected_profile: &String) -> Result<(Ini, Ini), String> {
-    let find_result = find_profile_with_name(config_file, selected_profile)
-    .and_then(compose(get_value_of_tuple, get_assume_settings));
+fn find_exact_match(profiles: &[String], pattern: &str) -> Option<String> {
+    let pattern = to_profile_name(pattern).to_lowercase();
+    let matches: Vec<&String> = profiles.iter()
+        .filter(|profile| profile.to_lowercase() == pattern)
+        .collect();
 
-    match find_result {
-        Some(settings) => {
-            let updated_config_file = set_default_assume_settings(config_file, settings);
-            Ok((updated_config_file, credentials_file.clone()))
-        },
-        None => Err("".to_string())
+    match matches.as_slice() {
+        [profile] => Some(profile.to_string()),
+        _ => None
     }
 }
 
-fn set_default_settings(file: &Ini, (aws_access_key_id, aws_secret_access_key): (&String, &String)) -> Ini {
+// assume role keys in the given list that the selected profile doesn't have are removed so that
+// nothing is left over from previously selected profile, other keys are only copied if the profile has them
+fn set_default_assume_settings(file: &Ini, properties: &Properties, keys: &[String]) -> (Ini, Ini) {
+    let mut output = file.clone();
+    for key in keys.iter().filter(|key| ASSUME_ROLE_KEYS.contains(&key.as_str())) {
+        match properties.get(key) {
+            Some(value) => output.set_to(Some("default"), key.to_string(), value.to_string()),
+            None => { output.delete_from(Some("default"), key); }
+        }
+    }
+    copy_keys(&output, properties, keys)
+}
+
+pub fn remove_default_assume_settings(file: &Ini) -> Ini {
+    let mut output = file.clone();
+    for key in ASSUME_ROLE_KEYS.iter() {
+        output.delete_from(Some("default"), key);
+    }
+    output
+}
+
+type SetResult = Result<(Ini, Ini, Ini), RawsError>;
+
+// returns None if selected profile is not an assume role profile
+fn set_assume_profile(config_file: &Ini, credentials_file: &Ini, selected_profile: &str, keys: &[String]) -> Option<SetResult> {
+    find_config_profile(config_file, selected_profile)
+        .filter(|properties| get_assume_settings(properties).is_some())
+        .map(|properties| {
+            // AWS CLI can't assume the role if source_profile chain is broken, e.g. after a profile is renamed
+            resolve_source_profiles(config_file, credentials_file, selected_profile)?;
+            let (updated_config_file, copied_keys_file) = set_default_assume_settings(config_file, properties, keys);
+            Ok((updated_config_file, credentials_file.clone(), copied_keys_file))
+        })
+}
+
+// session keys that the selected profile doesn't have are removed so that a token of previously selected profile
+// is not used with the new keys
+fn set_default_settings(file: &Ini, properties: &Properties, (aws_access_key_id, aws_secret_access_key): (&String, &String)) -> Ini {
     let mut output = file.clone();
     output.set_to(Some("default"), "aws_access_key_id".to_string(), aws_access_key_id.to_string());
     output.set_to(Some("default"), "aws_secret_access_key".to_string(), aws_secret_access_key.to_string());
+    for key in SESSION_KEYS.iter() {
+        match properties.get(*key) {
+            Some(value) => output.set_to(Some("default"), key.to_string(), value.to_string()),
+            None => { output.delete_from(Some("default"), key); }
+        }
+    }
     output
 }
 
-fn get_profile_settings(properties: &Properties) -> Option<(&String, &String)> {
+pub fn get_profile_settings(properties: &Properties) -> Option<(&String, &String)> {
     let aws_access_key_id = properties.get("aws_access_key_id");
     let aws_secret_access_key= properties.get("aws_secret_access_key");
     if let (Some(key_id), Some(access_key)) = (aws_access_key_id, aws_secret_access_key) {
@@ -59,54 +128,91 @@ fn get_profile_settings(properties: &Properties) -> Option<(&String, &String)> {
     None
 }
 
-fn set_profile(config_file: &Ini, credentials_file: &Ini, selected_profile: &String) -> Result<(Ini, Ini), String> {
-    let find_result = find_profile_with_name(credentials_file, selected_profile)
-        .and_then(compose(get_value_of_tuple, get_profile_settings));
+fn set_profile(config_file: &Ini, credentials_file: &Ini, selected_profile: &str) -> SetResult {
+    let find_result = find_credentials_profile(credentials_file, selected_profile)
+        .and_then(|properties| get_profile_settings(properties).map(|settings| (properties, settings)));
 
     match find_result {
-        Some(settings) => {
-            let updated_config_file = set_default_assume_settings(config_file, (&"".to_string(), &"".to_string()));
-            let updated_credentials_file = set_default_settings(credentials_file, settings);
-            Ok((updated_config_file, updated_credentials_file))
+        Some((properties, settings)) => {
+            let updated_config_file = remove_default_assume_settings(config_file);
+            let updated_credentials_file = set_default_settings(credentials_file, properties, settings);
+            Ok((updated_config_file, updated_credentials_file, Ini::new()))
         }
-        None => Err(format!("profile [{}] not found in both config and credentials file", selected_profile))
+        None => Err(RawsError::ProfileNotFound { profile: selected_profile.to_string() })
     }
 }
 
-pub fn handle(config: SetConfig,
-              mut choose_profile: impl FnMut(Vec<String>) -> Result<String, Box<Error>>,
-              mut write_to_file: impl FnMut(Ini, &String) -> Result<(), Box<Error>>)
-              -> Result<String, Box<Error>> {
-    let config_file = load_ini(&config.config_path)?;
-    let credentials_file = load_ini(&config.credentials_path)?;
-
-    let mut profiles = get_all_profile_names_except_default(&credentials_file,
-                                                        vec!("aws_access_key_id".to_string(), "aws_secret_access_key".to_string()));
-    profiles.extend(get_all_profile_names_except_default(&config_file,
-                                                       vec!("role_arn".to_string(), "source_profile".to_string())));
+pub fn select_profile(config_file: &Ini,
+                      credentials_file: &Ini,
+                      pattern: &str,
+                      choose_profile: &mut impl FnMut(Vec<String>, &Ini, &Ini) -> Result<PickerResult, Box<dyn Error>>)
+                      -> Result<PickerResult, Box<dyn Error>> {
+    let profiles = get_all_profile_names(config_file, credentials_file);
 
-
-    let selected_profile  = choose_profile(profiles)?;
-    if selected_profile.is_empty() {
-       return Ok(String::new()) ;
+    match find_exact_match(&profiles, pattern) {
+        Some(profile) => Ok(PickerResult::Selected(profile)),
+        None => choose_profile(profiles, config_file, credentials_file)
     }
+}
 
-    let set_result = set_assume_profile(&config_file, &credentials_file, &selected_profile)
-                        .or_else(
-                     |_| set_profile(&config_file, &credentials_file, &selected_profile))
+pub fn handle(config: SetConfig,
+              mut choose_profile: impl FnMut(Vec<String>, &Ini, &Ini) -> Result<PickerResult, Box<dyn Error>>,
+              mut write_to_files: impl FnMut(Vec<(Ini, String)>) -> Result<(), Box<dyn Error>>)
+              -> Result<String, Box<dyn Error>> {
+    let config_file = load_ini(&config.config_path)?;
+    let credentials_file = load_ini(&config.credentials_path)?;
+    let history = load_history(&config.history_path)?;
+    let current_profile = find_current_profile_name(&config_file, &credentials_file, &config.copy_keys);
+
+    let pattern = if config.pattern == PREVIOUS_PROFILE_PATTERN {
+        find_previous_profile(&history, &current_profile)
+            .ok_or_else(|| RawsError::Other(String::from("no previous profile found in history")))?
+    } else {
+        config.pattern.clone()
+    };
+
+    // default profile is left as it is if selection is cancelled,
+    // custom pickers may print the profile with `profile ` prefix
+    let selected_profile = match select_profile(&config_file, &credentials_file, &pattern, &mut choose_profile)? {
+        PickerResult::Selected(profile) => to_profile_name(&profile),
+        PickerResult::Cancelled => return Err(RawsError::PickerCancelled.into())
+    };
+
+    // values copied from previously selected profile are reverted first so that the user's own values come back
+    let copied_keys_file = load_ini(&config.copied_keys_path)?;
+    let reverted_config_file = revert_copied_keys(&config_file, &copied_keys_file);
+    let set_result = set_assume_profile(&reverted_config_file, &credentials_file, &selected_profile, &config.copy_keys)
+                        .unwrap_or_else(
+                     || set_profile(&reverted_config_file, &credentials_file, &selected_profile))
                         .map_err(|e| e.into());
 
-    set_result.and_then(|(updated_config_file, updated_credentials_file)| {
-        write_to_file(updated_config_file, &config.config_path)?;
-        write_to_file(updated_credentials_file, &config.credentials_path)?;
-        Ok(format!("default aws profile is set to [{}]", &selected_profile))
+    set_result.and_then(|(updated_config_file, updated_credentials_file, updated_copied_keys_file)| {
+        let mut files = vec!(
+            (updated_config_file, config.config_path.clone()),
+            (updated_credentials_file, config.credentials_path.clone()),
+        );
+        if updated_copied_keys_file.to_string() != copied_keys_file.to_string() {
+            files.push((updated_copied_keys_file, config.copied_keys_path.clone()));
+        }
+        write_to_files(files)?;
+
+        // default profile is already switched, so failing to record it in history only deserves a warning
+        let history = match current_profile {
+            Some(ref profile) => add_to_history(history, profile),
+            None => history
+        };
+        let mut messages = vec!(format!("default aws profile is set to [{}]", &selected_profile));
+        if let Err(e) = save_history(&config.history_path, &add_to_history(history, &selected_profile)) {
+            messages.push(format!("warning: profile history is not updated: {}", e));
+        }
+        Ok(messages.join("\n"))
     })
 }
 
 #[cfg(test)]
 mod tests {
     mod get_all_profile_names_except_default {
-        use ini::Ini;
+        use aws_ini::Ini;
         use handlers::set;
 
         #[test]
@@ -135,10 +241,77 @@ mod tests {
 
             assert_eq!(vec!("b", "d"), profiles)
         }
+
+        #[test]
+        fn strip_profile_prefix_from_section_names() {
+            let mut conf = Ini::new();
+            conf.with_section(Some("profile b".to_string())).set("role_arn", "arn_b");
+            conf.with_section(Some("profile default".to_string())).set("role_arn", "arn_default");
+            conf.with_section(Some("profile a".to_string())).set("role_arn", "arn_a");
+
+            let profiles = set::get_all_profile_names_except_default(&conf, vec!());
+
+            assert_eq!(vec!("a", "b"), profiles)
+        }
+    }
+
+    mod get_all_profile_names {
+        use aws_ini::Ini;
+        use handlers::set;
+
+        #[test]
+        fn list_profile_found_in_both_files_once() {
+            let mut config_file = Ini::new();
+            config_file.with_section(Some("profile both")).set("role_arn", "1").set("source_profile", "static");
+            config_file.with_section(Some("profile assumed")).set("role_arn", "2").set("source_profile", "static");
+            let mut credentials_file = Ini::new();
+            credentials_file.with_section(Some("static")).set("aws_access_key_id", "1").set("aws_secret_access_key", "1");
+            credentials_file.with_section(Some("Both")).set("aws_access_key_id", "2").set("aws_secret_access_key", "2");
+
+            let profiles = set::get_all_profile_names(&config_file, &credentials_file);
+
+            assert_eq!(vec!("Both", "static", "assumed"), profiles)
+        }
+    }
+
+    mod find_exact_match {
+        use handlers::set;
+
+        fn get_test_profiles() -> Vec<String> {
+            vec!("first_profile".to_string(), "second_profile".to_string(), "First_Assumed".to_string())
+        }
+
+        #[test]
+        fn return_profile_if_pattern_matches_exactly_ignoring_case() {
+            let result = set::find_exact_match(&get_test_profiles(), "first_ASSUMED");
+
+            assert_eq!(Some("First_Assumed".to_string()), result);
+        }
+
+        #[test]
+        fn ignore_profile_prefix_of_pattern() {
+            let result = set::find_exact_match(&get_test_profiles(), "PROFILE first_assumed");
+
+            assert_eq!(Some("First_Assumed".to_string()), result);
+        }
+
+        #[test]
+        fn return_none_if_pattern_only_matches_partially() {
+            let result = set::find_exact_match(&get_test_profiles(), "profile");
+
+            assert!(result.is_none());
+        }
+
+        #[test]
+        fn return_none_if_pattern_is_empty() {
+            let result = set::find_exact_match(&get_test_profiles(), "");
+
+            assert!(result.is_none());
+        }
     }
 
     mod set_default_assume_settings {
-        use ini::Ini;
+        use aws_ini::{ Ini, Properties };
         use handlers::set;
 
         #[test]
@@ -146,18 +319,76 @@ mod tests {
             let mut conf = Ini::new();
             conf.with_section(Some("default".to_string())).set("role_arn", "arn_default");
             conf.with_section(Some("default".to_string())).set("source_profile", "source_profile_default");
+            let mut properties = Properties::new();
+            properties.insert("role_arn".to_string(), "updated_arn".to_string());
+            properties.insert("source_profile".to_string(), "updated_source_profile".to_string());
 
-            let updated_conf = set::set_default_assume_settings(&conf,
-                                                                (&"updated_arn".to_string(), &"updated_source_profile".to_string()));
+            let keys = ["role_arn".to_string(), "source_profile".to_string()];
+            let (updated_conf, _) = set::set_default_assume_settings(&conf, &properties, &keys);
 
             assert_eq!(Some("updated_arn"), updated_conf.get_from(Some("default"), "role_arn"));
             assert_eq!(Some("updated_source_profile"), updated_conf.get_from(Some("default"), "source_profile"));
         }
+
+        #[test]
+        fn copy_only_provided_keys_and_remove_assume_role_keys_missing_from_selected_profile() {
+            let mut conf = Ini::new();
+            conf.with_section(Some("default"))
+                .set("role_arn", "arn_default")
+                .set("mfa_serial", "mfa_default")
+                .set("cli_pager", "less");
+            let mut properties = Properties::new();
+            properties.insert("role_arn".to_string(), "updated_arn".to_string());
+            properties.insert("region".to_string(), "us-east-1".to_string());
+            properties.insert("not_copied".to_string(), "value".to_string());
+
+            let (updated_conf, copied_keys_file) = set::set_default_assume_settings(&conf,
+                                                                &properties,
+                                                                &["role_arn".to_string(), "mfa_serial".to_string(), "region".to_string()]);
+
+            assert_eq!("[default]\nrole_arn = updated_arn\ncli_pager = less\nregion = us-east-1\n", updated_conf.to_string());
+            assert_eq!("[copied]\nregion = us-east-1\n", copied_keys_file.to_string());
+        }
+
+        #[test]
+        fn keep_keys_missing_from_selected_profile_that_are_not_assume_role_keys() {
+            let mut conf = Ini::new();
+            conf.with_section(Some("default")).set("region", "ap-southeast-2").set("output", "json");
+            let mut properties = Properties::new();
+            properties.insert("role_arn".to_string(), "updated_arn".to_string());
+            properties.insert("output".to_string(), "text".to_string());
+
+            let (updated_conf, _) = set::set_default_assume_settings(&conf,
+                                                                &properties,
+                                                                &["role_arn".to_string(), "region".to_string(), "output".to_string()]);
+
+            assert_eq!("[default]\nregion = ap-southeast-2\noutput = text\nrole_arn = updated_arn\n", updated_conf.to_string());
+        }
+    }
+
+    mod remove_default_assume_settings {
+        use aws_ini::Ini;
+        use handlers::set;
+
+        #[test]
+        fn remove_assume_role_keys_from_default_profile_only() {
+            let mut conf = Ini::new();
+            conf.with_section(Some("default"))
+                .set("region", "us-east-1")
+                .set("role_arn", "arn_default")
+                .set("source_profile", "source_profile_default")
+                .set("mfa_serial", "mfa_default");
+            conf.with_section(Some("profile assumed")).set("role_arn", "arn_assumed");
+
+            let updated_conf = set::remove_default_assume_settings(&conf);
+
+            assert_eq!("[default]\nregion = us-east-1\n\n[profile assumed]\nrole_arn = arn_assumed\n", updated_conf.to_string());
+        }
     }
 
     mod set_default_settings {
         use handlers::set;
-        use ini::Ini;
+        use aws_ini::{ Ini, Properties };
 
         #[test]
         fn set_default_profile_with_provided_values() {
@@ -166,11 +397,39 @@ mod tests {
             conf.with_section(Some("default".to_string())).set("aws_secret_access_key", "default_secret_access_key");
 
             let updated_conf = set::set_default_settings(&conf,
-                                                                (&"updated_key_id".to_string(), &"updated_secret_access_key".to_string()));
+                                                         &Properties::new(),
+                                                         (&"updated_key_id".to_string(), &"updated_secret_access_key".to_string()));
 
             assert_eq!(Some("updated_key_id"), updated_conf.get_from(Some("default"), "aws_access_key_id"));
             assert_eq!(Some("updated_secret_access_key"), updated_conf.get_from(Some("default"), "aws_secret_access_key"));
         }
+
+        #[test]
+        fn copy_session_token_with_its_expiry() {
+            let conf = Ini::new();
+            let mut properties = Properties::new();
+            properties.insert("aws_session_token".to_string(), "token".to_string());
+            properties.insert("x_security_token_expires".to_string(), "2026-10-18T10:00:00Z".to_string());
+
+            let updated_conf = set::set_default_settings(&conf, &properties, (&"key_id".to_string(), &"secret".to_string()));
+
+            assert_eq!(Some("token"), updated_conf.get_from(Some("default"), "aws_session_token"));
+            assert_eq!(Some("2026-10-18T10:00:00Z"), updated_conf.get_from(Some("default"), "x_security_token_expires"));
+        }
+
+        #[test]
+        fn remove_session_token_of_previous_profile() {
+            let mut conf = Ini::new();
+            conf.with_section(Some("default"))
+                .set("aws_access_key_id", "old_key_id")
+                .set("aws_secret_access_key", "old_secret")
+                .set("aws_session_token", "old_token")
+                .set("x_security_token_expires", "2026-10-18T10:00:00Z");
+
+            let updated_conf = set::set_default_settings(&conf, &Properties::new(), (&"key_id".to_string(), &"secret".to_string()));
+
+            assert_eq!("[default]\naws_access_key_id = key_id\naws_secret_access_key = secret\n", updated_conf.to_string());
+        }
     }
 
     mod get_profile_settings {
@@ -0,0 +1,181 @@
+use handlers::common::{ find_config_profile, find_credentials_profile, get_assume_settings, to_profile_name };
+use handlers::set::get_profile_settings;
+use aws_ini::Ini;
+use error::RawsError;
+
+#[derive(Debug, PartialEq)]
+pub struct SourceProfileChain {
+    // profiles followed through source_profile, not including the resolved profile itself
+    pub source_profiles: Vec<String>,
+    // profile whose static credentials are used at the end of the chain,
+    // none if the last profile gets credentials some other way, e.g. credential_source or SSO
+    pub credentials_profile: Option<String>,
+}
+
+pub fn is_same_profile(first: &str, second: &str) -> bool {
+    first.to_lowercase() == second.to_lowercase()
+}
+
+fn has_static_credentials(credentials_file: &Ini, name: &str) -> bool {
+    find_credentials_profile(credentials_file, name).and_then(get_profile_settings).is_some()
+}
+
+pub fn profile_exists(config_file: &Ini, credentials_file: &Ini, name: &str) -> bool {
+    find_config_profile(config_file, name).is_some() || find_credentials_profile(credentials_file, name).is_some()
+}
+
+// follows source_profile the same way AWS CLI does: a source profile with static credentials ends the chain
+// even if it has a role_arn too, only the selected profile itself is assumed despite its static credentials
+// unless it's its own source profile
+pub fn resolve_source_profiles(config_file: &Ini, credentials_file: &Ini, name: &str) -> Result<SourceProfileChain, RawsError> {
+    let name = to_profile_name(name);
+    if !profile_exists(config_file, credentials_file, &name) {
+        return Err(RawsError::ProfileNotFound { profile: name });
+    }
+
+    let mut chain = vec!(name);
+    loop {
+        let current = chain[chain.len() - 1].clone();
+        if chain.len() > 1 && has_static_credentials(credentials_file, &current) {
+            break;
+        }
+        let source_profile = match find_config_profile(config_file, &current).and_then(get_assume_settings) {
+            Some((_, source_profile)) => to_profile_name(source_profile),
+            None => break
+        };
+
+        if is_same_profile(&source_profile, &current) && has_static_credentials(credentials_file, &current) {
+            break;
+        }
+        if let Some(index) = chain.iter().position(|profile| is_same_profile(profile, &source_profile)) {
+            let mut profiles = chain[index..].to_vec();
+            profiles.push(source_profile);
+            return Err(RawsError::SourceProfileCycle { profiles });
+        }
+        if !profile_exists(config_file, credentials_file, &source_profile) {
+            return Err(RawsError::SourceProfileNotFound { profile: current, source_profile });
+        }
+        chain.push(source_profile);
+    }
+
+    let credentials_profile = chain.last()
+        .filter(|profile| has_static_credentials(credentials_file, profile))
+        .cloned();
+    Ok(SourceProfileChain { source_profiles: chain.split_off(1), credentials_profile })
+}
+
+// profiles including default profile whose source_profile is the given profile,
+// a profile that is its own source profile is not included
+pub fn find_referencing_profiles(config_file: &Ini, name: &str) -> Vec<String> {
+    config_file.iter()
+        .filter_map(|(section, properties)| section.as_ref().map(|section_name| (to_profile_name(section_name), properties)))
+        .filter(|(profile, properties)|
+            !is_same_profile(profile, name) &&
+            properties.get("source_profile").is_some_and(|source_profile| is_same_profile(&to_profile_name(source_profile), name)))
+        .map(|(profile, _)| profile)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    mod resolve_source_profiles {
+        use aws_ini::Ini;
+        use error::RawsError;
+        use handlers::source_profile::resolve_source_profiles;
+
+        fn get_credentials_file() -> Ini {
+            let mut credentials_file = Ini::new();
+            credentials_file.with_section(Some("static")).set("aws_access_key_id", "key").set("aws_secret_access_key", "secret");
+            credentials_file
+        }
+
+        #[test]
+        fn follow_source_profiles_until_static_credentials() {
+            let mut config_file = Ini::new();
+            config_file.with_section(Some("profile first")).set("role_arn", "1").set("source_profile", "second");
+            config_file.with_section(Some("profile second")).set("role_arn", "2").set("source_profile", "static");
+
+            let result = resolve_source_profiles(&config_file, &get_credentials_file(), "first").unwrap();
+
+            assert_eq!(vec!("second", "static"), result.source_profiles);
+            assert_eq!(Some("static".to_string()), result.credentials_profile);
+        }
+
+        #[test]
+        fn prefer_static_credentials_of_source_profile_over_its_role() {
+            let mut config_file = Ini::new();
+            config_file.with_section(Some("profile first")).set("role_arn", "1").set("source_profile", "static");
+            config_file.with_section(Some("profile static")).set("role_arn", "2").set("source_profile", "other");
+            let mut credentials_file = get_credentials_file();
+            credentials_file.with_section(Some("other")).set("aws_access_key_id", "key").set("aws_secret_access_key", "secret");
+
+            let result = resolve_source_profiles(&config_file, &credentials_file, "first").unwrap();
+
+            assert_eq!(vec!("static"), result.source_profiles);
+            assert_eq!(Some("static".to_string()), result.credentials_profile);
+        }
+
+        #[test]
+        fn stop_at_profile_that_is_its_own_source_profile() {
+            let mut config_file = Ini::new();
+            config_file.with_section(Some("profile static")).set("role_arn", "1").set("source_profile", "static");
+
+            let result = resolve_source_profiles(&config_file, &get_credentials_file(), "static").unwrap();
+
+            assert!(result.source_profiles.is_empty());
+            assert_eq!(Some("static".to_string()), result.credentials_profile);
+        }
+
+        #[test]
+        fn return_no_credentials_profile_if_chain_ends_without_static_credentials() {
+            let mut config_file = Ini::new();
+            config_file.with_section(Some("profile first")).set("role_arn", "1").set("source_profile", "sso");
+            config_file.with_section(Some("profile sso")).set("sso_start_url", "https://example.awsapps.com/start");
+
+            let result = resolve_source_profiles(&config_file, &Ini::new(), "first").unwrap();
+
+            assert_eq!(vec!("sso"), result.source_profiles);
+            assert!(result.credentials_profile.is_none());
+        }
+
+        #[test]
+        fn return_err_if_source_profile_not_found() {
+            let mut config_file = Ini::new();
+            config_file.with_section(Some("profile first")).set("role_arn", "1").set("source_profile", "second");
+            config_file.with_section(Some("profile second")).set("role_arn", "2").set("source_profile", "removed");
+
+            let result = resolve_source_profiles(&config_file, &get_credentials_file(), "first");
+
+            assert_eq!(Err(RawsError::SourceProfileNotFound { profile: "second".to_string(), source_profile: "removed".to_string() }), result);
+        }
+
+        #[test]
+        fn return_err_if_source_profiles_form_a_cycle() {
+            let mut config_file = Ini::new();
+            config_file.with_section(Some("profile first")).set("role_arn", "1").set("source_profile", "second");
+            config_file.with_section(Some("profile second")).set("role_arn", "2").set("source_profile", "third");
+            config_file.with_section(Some("profile third")).set("role_arn", "3").set("source_profile", "Second");
+
+            let result = resolve_source_profiles(&config_file, &get_credentials_file(), "first");
+
+            let profiles = vec!("second".to_string(), "third".to_string(), "Second".to_string());
+            assert_eq!(Err(RawsError::SourceProfileCycle { profiles }), result);
+        }
+    }
+
+    mod find_referencing_profiles {
+        use aws_ini::Ini;
+        use handlers::source_profile::find_referencing_profiles;
+
+        #[test]
+        fn return_profiles_with_given_source_profile_ignoring_case() {
+            let mut config_file = Ini::new();
+            config_file.with_section(Some("default")).set("role_arn", "1").set("source_profile", "static");
+            config_file.with_section(Some("profile first")).set("role_arn", "1").set("source_profile", "Static");
+            config_file.with_section(Some("profile second")).set("role_arn", "2").set("source_profile", "other");
+            config_file.with_section(Some("profile static")).set("role_arn", "3").set("source_profile", "static");
+
+            assert_eq!(vec!("default", "first"), find_referencing_profiles(&config_file, "static"));
+        }
+    }
+}
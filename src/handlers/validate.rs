@@ -0,0 +1,35 @@
+use handlers::common::{ find_config_profile, find_credentials_profile, load_ini };
+use handlers::set::get_all_profile_names;
+use handlers::source_profile::resolve_source_profiles;
+use aws_ini::Ini;
+use config::ValidateConfig;
+use error::RawsError;
+use std::error::Error;
+
+fn find_invalid_profiles(config_file: &Ini, credentials_file: &Ini) -> (usize, Vec<RawsError>) {
+    let mut profiles = get_all_profile_names(config_file, credentials_file);
+    // default profile may still have settings copied from a profile that was removed since
+    if find_config_profile(config_file, "default").is_some() || find_credentials_profile(credentials_file, "default").is_some() {
+        profiles.insert(0, "default".to_string());
+    }
+
+    let mut errors: Vec<RawsError> = vec!();
+    for profile in &profiles {
+        // profiles sourcing from a broken profile report the same error
+        match resolve_source_profiles(config_file, credentials_file, profile) {
+            Err(error) if !errors.contains(&error) => errors.push(error),
+            _ => ()
+        }
+    }
+    (profiles.len(), errors)
+}
+
+pub fn handle(config: ValidateConfig) -> Result<String, Box<dyn Error>> {
+    let config_file = load_ini(&config.config_path)?;
+    let credentials_file = load_ini(&config.credentials_path)?;
+
+    match find_invalid_profiles(&config_file, &credentials_file) {
+        (count, ref errors) if errors.is_empty() => Ok(format!("all {} profiles are valid", count)),
+        (_, errors) => Err(RawsError::InvalidProfiles { errors }.into())
+    }
+}
@@ -1,6 +1,12 @@
 extern crate clap;
-extern crate ini;
 extern crate shellexpand;
+#[macro_use]
+extern crate serde_json;
+#[cfg(unix)]
+extern crate libc;
 
 pub mod handlers;
+pub mod aws_ini;
 pub mod config;
+pub mod file_transaction;
+pub mod error;
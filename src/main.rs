@@ -1,34 +1,75 @@
 #[macro_use]
 extern crate clap;
 extern crate raws;
-extern crate ini;
-extern crate shellexpand;
 
 use clap::{App, AppSettings};
-use ini::Ini;
+use raws::aws_ini::Ini;
 
-use raws::config::Config;
-use raws::handlers::{get, set, fzf};
+use raws::config::{ Config, ErrorFormat, PickerKind, get_error_format };
+use raws::error;
+use raws::file_transaction;
+use raws::handlers::picker::PickerOptions;
+use raws::handlers::{get, set, picker, list, describe, validate, add, add_role, remove, rename, prompt, env, history, restore_backup};
 use std::error::Error;
-use shellexpand::tilde;
+use std::process;
 
-const VERSION: &'static str = env!("CARGO_PKG_VERSION");
+const VERSION: &str = env!("CARGO_PKG_VERSION");
 
-fn write_to_file(file: Ini, output_path: &String) -> Result<(), Box<Error>> {
-    file.write_to_file(tilde(output_path).to_string()).map_err(|e| e.into())
+fn write_to_files(files: Vec<(Ini, String)>) -> Result<(), Box<dyn Error>> {
+    let contents = files.into_iter()
+        .map(|(file, output_path)| (file.to_string(), output_path))
+        .collect();
+    file_transaction::write_files(contents)
 }
 
-fn execute_handler(config: Config) -> Result<String, Box<Error>> {
+fn picker_options(kind: &PickerKind, config_path: &str, credentials_path: &str) -> PickerOptions {
+    PickerOptions {
+        kind: kind.clone(),
+        config_path: config_path.to_string(),
+        credentials_path: credentials_path.to_string(),
+    }
+}
+
+fn execute_handler(config: Config) -> Result<String, Box<dyn Error>> {
     match config {
         Config::Get(config) => get::handle(config),
-        Config::Set(config) => set::handle(config, fzf::choose_profile, write_to_file),
+        Config::Set(config) => {
+            let (pattern, options) = (config.pattern.clone(), picker_options(&config.picker, &config.config_path, &config.credentials_path));
+            set::handle(config, |profiles, config_file, credentials_file| picker::choose_profile(&options, config_file, credentials_file, profiles, &pattern), write_to_files)
+        },
+        Config::List(config) => list::handle(config),
+        Config::Describe(config) => describe::handle(config),
+        Config::Validate(config) => validate::handle(config),
+        Config::Add(config) => add::handle(config, prompt::read_input, write_to_files),
+        Config::AddRole(config) => {
+            let options = picker_options(&config.picker, &config.config_path, &config.credentials_path);
+            add_role::handle(config, prompt::read_input, |profiles, config_file, credentials_file| picker::choose_profile(&options, config_file, credentials_file, profiles, ""), write_to_files)
+        },
+        Config::Remove(config) => remove::handle(config, write_to_files),
+        Config::Rename(config) => rename::handle(config, write_to_files),
+        Config::Env(config) => {
+            let (pattern, options) = (config.pattern.clone(), picker_options(&config.picker, &config.config_path, &config.credentials_path));
+            env::handle(config, |profiles, config_file, credentials_file| picker::choose_profile(&options, config_file, credentials_file, profiles, &pattern))
+        },
+        Config::History(config) => {
+            let options = picker_options(&config.picker, &config.config_path, &config.credentials_path);
+            history::handle(config, |profiles, config_file, credentials_file| picker::choose_profile(&options, config_file, credentials_file, profiles, ""), write_to_files)
+        },
+        Config::RestoreBackup(config) => restore_backup::handle(config),
     }
 }
 
-fn print_result(result: Result<String, Box<Error>>) {
+fn print_result(result: Result<String, Box<dyn Error>>, error_format: ErrorFormat) {
     match result {
         Ok(ref message) if !message.is_empty() => println!("{}", message),
-        Err(error) => println!("== Error: {}", error),
+        Err(error) => {
+            match error_format {
+                ErrorFormat::Text if error::is_cancelled(error.as_ref()) => (),
+                ErrorFormat::Text => eprintln!("== Error: {}", error),
+                ErrorFormat::Json => eprintln!("{}", error::to_json(error.as_ref())),
+            }
+            process::exit(error::exit_code_of(error.as_ref()));
+        },
         _ => ()
     };
 }
@@ -37,11 +78,12 @@ fn main() {
     let yaml = load_yaml!("cli.yaml");
     let  app = App::from_yaml(yaml)
         .version(VERSION)
-        .setting(AppSettings::ArgRequiredElseHelp);
+        .setting(AppSettings::SubcommandRequiredElseHelp);
     let matches = app.get_matches();
     let config = Config::new(&matches).unwrap();
+    let error_format = get_error_format(&matches);
 
     let result = execute_handler(config);
 
-    print_result(result);
+    print_result(result, error_format);
 }
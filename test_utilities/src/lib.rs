@@ -1,7 +1,15 @@
-use std::env::current_dir;
+use std::env::{ current_dir, temp_dir };
+use std::fs::remove_file;
+use std::process;
 
 pub fn get_test_data_path(file_name: String) -> String {
     let unwrapped = current_dir().unwrap();
     let current_dir = unwrapped.display();
     format!("{}/{}/{}", current_dir, "tests/test_data", file_name)
 }
+
+pub fn get_temp_file_path(file_name: &str) -> String {
+    let path = temp_dir().join(format!("raws_{}_{}", process::id(), file_name));
+    let _ = remove_file(&path);
+    path.display().to_string()
+}
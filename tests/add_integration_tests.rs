@@ -0,0 +1,120 @@
+extern crate raws;
+extern crate test_utilities;
+
+use raws::aws_ini::Ini;
+use raws::handlers::add;
+use raws::config;
+use raws::error::{ self, RawsError };
+use test_utilities::get_test_data_path;
+use std::error::Error;
+
+const ACCESS_KEY_ID: &str = "AKIAI44QH8DHBEXAMPLE";
+const SECRET_ACCESS_KEY: &str = "je7MtGbClwBF/2Zp9Utk/h3yCo8nvbEXAMPLEKEY";
+
+type HandleResult = (Result<String, Box<dyn Error>>, Vec<(String, bool)>, Vec<(Ini, String)>);
+
+fn get_config(profile: &str, force: bool) -> config::AddConfig {
+    config::AddConfig {
+        config_path: get_test_data_path("add.config".to_string()),
+        credentials_path: get_test_data_path("add.credentials".to_string()),
+        profile: profile.to_string(),
+        region: None,
+        output: None,
+        force,
+    }
+}
+
+fn execute_handle(config: config::AddConfig, inputs: Vec<&str>) -> HandleResult {
+    let mut prompts: Vec<(String, bool)> = vec!();
+    let mut updated_files: Vec<(Ini, String)> = vec!();
+
+    let result = {
+        let mut inputs = inputs.into_iter();
+        let read_input = |message: &str, hidden: bool| {
+            prompts.push((message.to_string(), hidden));
+            Ok(inputs.next().unwrap_or_default().to_string())
+        };
+
+        let write_to_files = |files: Vec<(Ini, String)>| {
+            updated_files.extend(files);
+            Ok(())
+        };
+
+        add::handle(config, read_input, write_to_files)
+    };
+
+    (result, prompts, updated_files)
+}
+
+#[test]
+fn add_profile_to_credentials_file_with_secret_prompted_without_echo() {
+    let (result, prompts, updated_files) = execute_handle(get_config("new_profile", false), vec!(ACCESS_KEY_ID, SECRET_ACCESS_KEY));
+
+    assert_eq!("aws profile [new_profile] is added", result.unwrap());
+    assert_eq!(vec!(("AWS Access Key ID".to_string(), false), ("AWS Secret Access Key".to_string(), true)), prompts);
+    assert_eq!(1, updated_files.len());
+    let (updated_credentials_file, path) = &updated_files[0];
+    assert_eq!(&get_test_data_path("add.credentials".to_string()), path);
+    assert_eq!(Some(ACCESS_KEY_ID), updated_credentials_file.get_from(Some("new_profile"), "aws_access_key_id"));
+    assert_eq!(Some(SECRET_ACCESS_KEY), updated_credentials_file.get_from(Some("new_profile"), "aws_secret_access_key"));
+}
+
+#[test]
+fn add_region_and_output_to_config_file_section_of_profile() {
+    let mut config = get_config("new_profile", false);
+    config.region = Some("ap-southeast-2".to_string());
+    config.output = Some("json".to_string());
+
+    let (result, _, updated_files) = execute_handle(config, vec!(ACCESS_KEY_ID, SECRET_ACCESS_KEY));
+
+    assert!(result.is_ok());
+    let (updated_config_file, path) = &updated_files[0];
+    assert_eq!(&get_test_data_path("add.config".to_string()), path);
+    assert_eq!(Some("ap-southeast-2"), updated_config_file.get_from(Some("profile new_profile"), "region"));
+    assert_eq!(Some("json"), updated_config_file.get_from(Some("profile new_profile"), "output"));
+}
+
+#[test]
+fn return_err_without_prompting_if_profile_exists() {
+    let (result, prompts, updated_files) = execute_handle(get_config("static_profile", false), vec!());
+
+    let error = result.unwrap_err();
+    assert_eq!("profile [static_profile] already exists, use --force to replace it", error.to_string());
+    assert_eq!(error::EXIT_INVALID_INPUT, error::exit_code_of(error.as_ref()));
+    assert!(prompts.is_empty());
+    assert!(updated_files.is_empty());
+}
+
+#[test]
+fn replace_keys_and_remove_session_token_of_existing_profile_with_force() {
+    let (result, _, updated_files) = execute_handle(get_config("static_profile", true), vec!(ACCESS_KEY_ID, SECRET_ACCESS_KEY));
+
+    assert_eq!("aws profile [static_profile] is replaced", result.unwrap());
+    let (updated_credentials_file, _) = &updated_files[0];
+    assert_eq!(Some(ACCESS_KEY_ID), updated_credentials_file.get_from(Some("Static_Profile"), "aws_access_key_id"));
+    assert!(updated_credentials_file.get_from(Some("Static_Profile"), "aws_session_token").is_none());
+    assert!(updated_credentials_file.section(Some("static_profile")).is_none());
+}
+
+#[test]
+fn replace_assume_role_profile_with_static_profile_with_force() {
+    let (result, _, updated_files) = execute_handle(get_config("assumed_profile", true), vec!(ACCESS_KEY_ID, SECRET_ACCESS_KEY));
+
+    assert!(result.is_ok());
+    let (updated_config_file, _) = &updated_files[0];
+    assert!(updated_config_file.get_from(Some("profile assumed_profile"), "role_arn").is_none());
+    assert!(updated_config_file.get_from(Some("profile assumed_profile"), "source_profile").is_none());
+    assert_eq!(Some("eu-west-1"), updated_config_file.get_from(Some("profile assumed_profile"), "region"));
+}
+
+#[test]
+fn return_err_without_writing_if_access_key_id_is_invalid() {
+    let (result, prompts, updated_files) = execute_handle(get_config("new_profile", false), vec!("ASIAI44QH8DHBEXAMPLE"));
+
+    match result.unwrap_err().downcast_ref::<RawsError>() {
+        Some(RawsError::InvalidValue { name, .. }) => assert_eq!("access key id", name),
+        other => panic!("unexpected error {:?}", other)
+    }
+    assert_eq!(1, prompts.len());
+    assert!(updated_files.is_empty());
+}
@@ -0,0 +1,164 @@
+extern crate raws;
+extern crate test_utilities;
+
+use raws::aws_ini::Ini;
+use raws::handlers::add_role;
+use raws::handlers::picker::PickerResult;
+use raws::config;
+use raws::error::{ self, RawsError };
+use test_utilities::get_test_data_path;
+use std::error::Error;
+
+const ROLE_ARN: &str = "arn:aws:iam::210987654321:role/other-role";
+
+type HandleResult = (Result<String, Box<dyn Error>>, Vec<String>, Vec<(Ini, String)>);
+
+fn get_config(profile: &str, source_profile: Option<&str>) -> config::AddRoleConfig {
+    config::AddRoleConfig {
+        config_path: get_test_data_path("add.config".to_string()),
+        credentials_path: get_test_data_path("add.credentials".to_string()),
+        profile: profile.to_string(),
+        role_arn: Some(ROLE_ARN.to_string()),
+        source_profile: source_profile.map(|profile| profile.to_string()),
+        mfa_serial: None,
+        external_id: None,
+        duration_seconds: None,
+        role_session_name: None,
+        region: None,
+        force: false,
+        picker: config::PickerKind::Auto,
+    }
+}
+
+fn execute_handle(config: config::AddRoleConfig, picker_result: PickerResult) -> HandleResult {
+    let mut profiles_to_choose: Vec<String> = vec!();
+    let mut updated_files: Vec<(Ini, String)> = vec!();
+
+    let result = {
+        let read_input = |_: &str, _: bool| Ok(ROLE_ARN.to_string());
+
+        let choose_profile = |profiles: Vec<String>, _: &Ini, _: &Ini| {
+            profiles_to_choose = profiles;
+            Ok(picker_result.clone())
+        };
+
+        let write_to_files = |files: Vec<(Ini, String)>| {
+            updated_files.extend(files);
+            Ok(())
+        };
+
+        add_role::handle(config, read_input, choose_profile, write_to_files)
+    };
+
+    (result, profiles_to_choose, updated_files)
+}
+
+#[test]
+fn add_role_profile_with_given_settings_to_config_file() {
+    let mut config = get_config("new_role", Some("static_profile"));
+    config.mfa_serial = Some("arn:aws:iam::123456789012:mfa/some-user".to_string());
+    config.external_id = Some("some-external-id".to_string());
+    config.duration_seconds = Some("3600".to_string());
+    config.role_session_name = Some("some-session".to_string());
+    config.region = Some("eu-central-1".to_string());
+
+    let (result, _, updated_files) = execute_handle(config, PickerResult::Cancelled);
+
+    assert_eq!("aws profile [new_role] is added", result.unwrap());
+    assert_eq!(1, updated_files.len());
+    let (updated_config_file, path) = &updated_files[0];
+    assert_eq!(&get_test_data_path("add.config".to_string()), path);
+    let section = Some("profile new_role");
+    assert_eq!(Some(ROLE_ARN), updated_config_file.get_from(section, "role_arn"));
+    assert_eq!(Some("static_profile"), updated_config_file.get_from(section, "source_profile"));
+    assert_eq!(Some("arn:aws:iam::123456789012:mfa/some-user"), updated_config_file.get_from(section, "mfa_serial"));
+    assert_eq!(Some("some-external-id"), updated_config_file.get_from(section, "external_id"));
+    assert_eq!(Some("3600"), updated_config_file.get_from(section, "duration_seconds"));
+    assert_eq!(Some("some-session"), updated_config_file.get_from(section, "role_session_name"));
+    assert_eq!(Some("eu-central-1"), updated_config_file.get_from(section, "region"));
+}
+
+#[test]
+fn select_source_profile_with_picker_if_not_given() {
+    let (result, profiles_to_choose, updated_files) = execute_handle(get_config("new_role", None), PickerResult::Selected("assumed_profile".to_string()));
+
+    assert!(result.is_ok());
+    assert_eq!(vec!("Static_Profile", "assumed_profile"), profiles_to_choose);
+    let (updated_config_file, _) = &updated_files[0];
+    assert_eq!(Some("assumed_profile"), updated_config_file.get_from(Some("profile new_role"), "source_profile"));
+}
+
+#[test]
+fn add_nothing_if_source_profile_selection_is_cancelled() {
+    let (result, _, updated_files) = execute_handle(get_config("new_role", None), PickerResult::Cancelled);
+
+    assert_eq!(error::EXIT_PICKER_CANCELLED, error::exit_code_of(result.unwrap_err().as_ref()));
+    assert!(updated_files.is_empty());
+}
+
+#[test]
+fn prompt_for_role_arn_if_not_given() {
+    let mut config = get_config("new_role", Some("static_profile"));
+    config.role_arn = None;
+
+    let (result, _, updated_files) = execute_handle(config, PickerResult::Cancelled);
+
+    assert!(result.is_ok());
+    let (updated_config_file, _) = &updated_files[0];
+    assert_eq!(Some(ROLE_ARN), updated_config_file.get_from(Some("profile new_role"), "role_arn"));
+}
+
+#[test]
+fn return_err_if_source_profile_not_found() {
+    let (result, _, updated_files) = execute_handle(get_config("new_role", Some("removed_profile")), PickerResult::Cancelled);
+
+    let error = result.unwrap_err();
+    assert_eq!("source profile [removed_profile] of profile [new_role] not found in both config and credentials file", error.to_string());
+    assert_eq!(error::EXIT_INVALID_PROFILE, error::exit_code_of(error.as_ref()));
+    assert!(updated_files.is_empty());
+}
+
+#[test]
+fn return_err_if_role_arn_is_invalid() {
+    let mut config = get_config("new_role", Some("static_profile"));
+    config.role_arn = Some("arn:aws:iam::210987654321:user/some-user".to_string());
+
+    let (result, _, updated_files) = execute_handle(config, PickerResult::Cancelled);
+
+    match result.unwrap_err().downcast_ref::<RawsError>() {
+        Some(RawsError::InvalidValue { name, .. }) => assert_eq!("role arn", name),
+        other => panic!("unexpected error {:?}", other)
+    }
+    assert!(updated_files.is_empty());
+}
+
+#[test]
+fn return_err_if_profile_exists_without_force() {
+    let (result, _, _) = execute_handle(get_config("Assumed_Profile", Some("static_profile")), PickerResult::Cancelled);
+
+    assert_eq!("profile [Assumed_Profile] already exists, use --force to replace it", result.unwrap_err().to_string());
+}
+
+#[test]
+fn replace_role_of_existing_profile_with_force() {
+    let mut config = get_config("assumed_profile", Some("static_profile"));
+    config.force = true;
+
+    let (result, _, updated_files) = execute_handle(config, PickerResult::Cancelled);
+
+    assert_eq!("aws profile [assumed_profile] is replaced", result.unwrap());
+    let (updated_config_file, _) = &updated_files[0];
+    assert_eq!(Some(ROLE_ARN), updated_config_file.get_from(Some("profile assumed_profile"), "role_arn"));
+    assert_eq!(Some("eu-west-1"), updated_config_file.get_from(Some("profile assumed_profile"), "region"));
+}
+
+#[test]
+fn return_err_if_replaced_profile_becomes_its_own_source_without_credentials() {
+    let mut config = get_config("assumed_profile", Some("assumed_profile"));
+    config.force = true;
+
+    let (result, _, updated_files) = execute_handle(config, PickerResult::Cancelled);
+
+    assert_eq!("source profiles form a cycle: assumed_profile -> assumed_profile", result.unwrap_err().to_string());
+    assert!(updated_files.is_empty());
+}
@@ -0,0 +1,121 @@
+extern crate raws;
+#[macro_use] extern crate serde_json;
+extern crate test_utilities;
+
+use raws::handlers::describe;
+use raws::config;
+use test_utilities::{ get_test_data_path };
+
+fn get_describe_config(profile: &str, output: config::DescribeFormat) -> config::DescribeConfig {
+    config::DescribeConfig {
+        config_path: get_test_data_path("describe.config".to_string()),
+        credentials_path: get_test_data_path("describe.credentials".to_string()),
+        profile: profile.to_string(),
+        output
+    }
+}
+
+#[test]
+fn describe_static_profile_with_masked_keys() {
+    let result = describe::handle(get_describe_config("first_profile", config::DescribeFormat::Text));
+
+    let expected = [
+        "profile           first_profile".to_string(),
+        "kind              static".to_string(),
+        format!("files             {}, {}", get_test_data_path("describe.config".to_string()), get_test_data_path("describe.credentials".to_string())),
+        "account id        -".to_string(),
+        "role name         -".to_string(),
+        "region            ap-southeast-2".to_string(),
+        "source profile    -".to_string(),
+        "credentials from  first_profile".to_string(),
+        "mfa               not required".to_string(),
+        "access key id     AKIA************MPLE".to_string(),
+        "".to_string(),
+        "config keys:".to_string(),
+        "  region  ap-southeast-2".to_string(),
+        "".to_string(),
+        "credentials keys:".to_string(),
+        "  aws_access_key_id      AKIA************MPLE".to_string(),
+        "  aws_secret_access_key  ********".to_string(),
+    ].join("\n");
+    assert_eq!(expected, result.unwrap());
+}
+
+#[test]
+fn describe_assume_role_profile_with_source_profile_chain() {
+    let result = describe::handle(get_describe_config("profile chained_profile", config::DescribeFormat::Text));
+
+    let expected = [
+        "profile           chained_profile".to_string(),
+        "kind              assume-role".to_string(),
+        format!("files             {}", get_test_data_path("describe.config".to_string())),
+        "account id        210987654321".to_string(),
+        "role name         other-role".to_string(),
+        "region            -".to_string(),
+        "source profile    assumed_profile -> first_profile".to_string(),
+        "credentials from  first_profile".to_string(),
+        "mfa               not required".to_string(),
+        "access key id     AKIA************MPLE".to_string(),
+        "".to_string(),
+        "config keys:".to_string(),
+        "  role_arn        arn:aws:iam::210987654321:role/other-role".to_string(),
+        "  source_profile  assumed_profile".to_string(),
+    ].join("\n");
+    assert_eq!(expected, result.unwrap());
+}
+
+#[test]
+fn describe_mfa_requirement() {
+    let result = describe::handle(get_describe_config("assumed_profile", config::DescribeFormat::Text));
+
+    assert!(result.unwrap().contains("mfa               required (arn:aws:iam::111111111111:mfa/some-user)"));
+}
+
+#[test]
+fn describe_nested_keys_under_the_key_they_belong_to() {
+    let result = describe::handle(get_describe_config("assumed_profile", config::DescribeFormat::Text));
+
+    let expected = [
+        "  region          eu-west-1",
+        "  role_arn        arn:aws:iam::123456789012:role/some-role",
+        "  s3",
+        "    addressing_style         path",
+        "    max_concurrent_requests  20",
+        "  source_profile  first_profile",
+    ].join("\n");
+    assert!(result.unwrap().contains(&expected));
+}
+
+#[test]
+fn describe_profile_as_json() {
+    let result = describe::handle(get_describe_config("assumed_profile", config::DescribeFormat::Json));
+
+    let value: serde_json::Value = serde_json::from_str(&result.unwrap()).unwrap();
+    assert_eq!("assume-role", value["kind"]);
+    assert_eq!("123456789012", value["account_id"]);
+    assert_eq!("some-role", value["role_name"]);
+    assert_eq!(json!(["first_profile"]), value["source_profiles"]);
+    assert_eq!("first_profile", value["credentials_profile"]);
+    assert_eq!("eu-west-1", value["keys"]["config"]["region"]);
+    assert_eq!(json!({ "addressing_style": "path", "max_concurrent_requests": "20" }), value["keys"]["config"]["s3"]);
+    assert_eq!(json!({}), value["keys"]["credentials"]);
+}
+
+#[test]
+fn describe_problem_of_profile_with_source_profile_cycle() {
+    let mut config = get_describe_config("first_cycle_profile", config::DescribeFormat::Text);
+    config.config_path = get_test_data_path("validate.config".to_string());
+
+    let result = describe::handle(config);
+
+    let expected = "problem           source profiles form a cycle: first_cycle_profile -> second_cycle_profile -> first_cycle_profile";
+    assert!(result.unwrap().contains(expected));
+}
+
+#[test]
+fn return_err_if_profile_not_found() {
+    let result = describe::handle(get_describe_config("not_existing", config::DescribeFormat::Text));
+
+    let error_message = format!("{}", result.unwrap_err());
+    assert_eq!("profile [not_existing] not found in both config and credentials file", error_message);
+}
@@ -0,0 +1,79 @@
+extern crate raws;
+extern crate test_utilities;
+
+use raws::handlers::env;
+use raws::config;
+use raws::error;
+use raws::handlers::picker::PickerResult;
+use test_utilities::{ get_test_data_path };
+
+fn get_env_config(pattern: &str, shell: config::Shell) -> config::EnvConfig {
+    config::EnvConfig {
+        config_path: get_test_data_path("set.config".to_string()),
+        credentials_path: get_test_data_path("set.credentials".to_string()),
+        pattern: pattern.to_string(),
+        shell,
+        picker: config::PickerKind::Auto
+    }
+}
+
+#[test]
+fn export_aws_profile_and_unset_static_credentials_if_selected_profile_is_assumed_profile() {
+    let config = get_env_config("", config::Shell::Posix);
+
+    let result = env::handle(config, |_, _, _| Ok(PickerResult::Selected("first_assumed_profile".to_string())));
+
+    let expected = [
+        "export AWS_PROFILE='first_assumed_profile'",
+        "unset AWS_ACCESS_KEY_ID",
+        "unset AWS_SECRET_ACCESS_KEY",
+        "unset AWS_SESSION_TOKEN",
+    ].join("\n");
+    assert_eq!(expected, result.unwrap());
+}
+
+#[test]
+fn export_static_credentials_if_selected_profile_is_in_credentials_only() {
+    let config = get_env_config("first_profile", config::Shell::Fish);
+
+    let result = env::handle(config, |_, _, _| panic!("fzf should not be called for exact match"));
+
+    let expected = [
+        "set -gx AWS_PROFILE 'first_profile';",
+        "set -gx AWS_ACCESS_KEY_ID '1';",
+        "set -gx AWS_SECRET_ACCESS_KEY '1';",
+        "set -e AWS_SESSION_TOKEN;",
+    ].join("\n");
+    assert_eq!(expected, result.unwrap());
+}
+
+#[test]
+fn export_session_token_of_temporary_credentials() {
+    let mut config = get_env_config("session_profile", config::Shell::Posix);
+    config.credentials_path = get_test_data_path("set_temporary.credentials".to_string());
+
+    let result = env::handle(config, |_, _, _| panic!("fzf should not be called for exact match"));
+
+    assert!(result.unwrap().ends_with("export AWS_SESSION_TOKEN='new_token'"));
+}
+
+#[test]
+fn return_cancelled_err_if_select_profiles_action_is_cancelled() {
+    let config = get_env_config("", config::Shell::PowerShell);
+
+    let result = env::handle(config, |_, _, _| Ok(PickerResult::Cancelled));
+
+    let error = result.unwrap_err();
+    assert!(error::is_cancelled(error.as_ref()));
+    assert_eq!(error::EXIT_PICKER_CANCELLED, error::exit_code_of(error.as_ref()));
+}
+
+#[test]
+fn return_err_if_profile_is_not_in_both_config_and_credentials() {
+    let config = get_env_config("", config::Shell::Posix);
+
+    let result = env::handle(config, |_, _, _| Ok(PickerResult::Selected("third_profile".to_string())));
+
+    let error_message = format!("{}", result.unwrap_err());
+    assert!(error_message.contains("profile [third_profile] not found"));
+}
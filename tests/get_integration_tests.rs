@@ -5,24 +5,32 @@ use raws::handlers::get;
 use raws::config;
 use test_utilities::{ get_test_data_path };
 
+fn get_default_copy_keys() -> Vec<String> {
+    config::DEFAULT_COPY_KEYS.iter().map(|key| key.to_string()).collect()
+}
+
 #[test]
 fn return_assumed_profile_if_matching_profile_found_in_both_credentials_and_profile() {
     let config = config::GetConfig {
         config_path: get_test_data_path("get_matching_found_in_both.config".to_string()),
-        credentials_path: get_test_data_path("get_matching_found_in_both.credentials".to_string())
+        credentials_path: get_test_data_path("get_matching_found_in_both.credentials".to_string()),
+        copy_keys: get_default_copy_keys(),
+        all: false
     };
 
     let result = get::handle(config);
 
     assert!(result.is_ok());
-    assert_eq!("profile second_assumed_profile", result.unwrap());
+    assert_eq!("second_assumed_profile", result.unwrap());
 }
 
 #[test]
 fn return_profile_from_credentials_if_profile_found_in_credentials_only() {
     let config = config::GetConfig {
         config_path: get_test_data_path("get_matching_found_in_credentials_only.config".to_string()),
-        credentials_path: get_test_data_path("get_matching_found_in_credentials_only.credentials".to_string())
+        credentials_path: get_test_data_path("get_matching_found_in_credentials_only.credentials".to_string()),
+        copy_keys: get_default_copy_keys(),
+        all: false
     };
 
     let result = get::handle(config);
@@ -35,7 +43,9 @@ fn return_profile_from_credentials_if_profile_found_in_credentials_only() {
 fn return_err_if_not_found_in_both_config_and_credentials() {
     let config = config::GetConfig {
         config_path: get_test_data_path("get_not_found_in_both.config".to_string()),
-        credentials_path: get_test_data_path("get_not_found_in_both.credentials".to_string())
+        credentials_path: get_test_data_path("get_not_found_in_both.credentials".to_string()),
+        copy_keys: get_default_copy_keys(),
+        all: false
     };
 
     let result = get::handle(config);
@@ -45,29 +55,105 @@ fn return_err_if_not_found_in_both_config_and_credentials() {
 }
 
 #[test]
-fn return_err_if_config_file_not_found() {
+fn return_profile_matching_all_settings_of_default_profile() {
     let config = config::GetConfig {
-        config_path: get_test_data_path("not_existing.config".to_string()),
-        credentials_path: get_test_data_path("get_not_found_in_both.credentials".to_string())
+        config_path: get_test_data_path("get_disambiguated_by_region.config".to_string()),
+        credentials_path: get_test_data_path("get_disambiguated_by_region.credentials".to_string()),
+        copy_keys: get_default_copy_keys(),
+        all: false
     };
 
     let result = get::handle(config);
 
-    let error_message = format!("{}", result.unwrap_err());
-    assert!(error_message.contains("failed to load file"));
-    assert!(error_message.contains("not_existing.config"));
+    assert!(result.is_ok());
+    assert_eq!("second_assumed_profile", result.unwrap());
 }
 
 #[test]
-fn return_err_if_credentials_file_not_found() {
+fn match_assume_role_profiles_by_configured_copy_keys_only() {
     let config = config::GetConfig {
-        config_path: get_test_data_path("get_not_found_in_both.config".to_string()),
-        credentials_path: get_test_data_path("not_existing.credentials".to_string())
+        config_path: get_test_data_path("get_disambiguated_by_region.config".to_string()),
+        credentials_path: get_test_data_path("get_disambiguated_by_region.credentials".to_string()),
+        copy_keys: vec!("role_arn".to_string(), "source_profile".to_string()),
+        all: true
+    };
+
+    let result = get::handle(config);
+
+    assert_eq!("first_assumed_profile\nsecond_assumed_profile", result.unwrap());
+}
+
+#[test]
+fn return_err_if_default_profile_matches_multiple_profiles() {
+    let config = config::GetConfig {
+        config_path: get_test_data_path("get_ambiguous.config".to_string()),
+        credentials_path: get_test_data_path("get_ambiguous.credentials".to_string()),
+        copy_keys: get_default_copy_keys(),
+        all: false
     };
 
     let result = get::handle(config);
 
     let error_message = format!("{}", result.unwrap_err());
-    assert!(error_message.contains("failed to load file"));
-    assert!(error_message.contains("not_existing.credentials"));
+    assert!(error_message.contains("default profile matches multiple profiles"));
+    assert!(error_message.contains("first_assumed_profile, second_assumed_profile"));
+}
+
+#[test]
+fn return_all_matching_profiles_if_all_flag_is_set() {
+    let config = config::GetConfig {
+        config_path: get_test_data_path("get_ambiguous.config".to_string()),
+        credentials_path: get_test_data_path("get_ambiguous.credentials".to_string()),
+        copy_keys: get_default_copy_keys(),
+        all: true
+    };
+
+    let result = get::handle(config);
+
+    assert!(result.is_ok());
+    assert_eq!("first_assumed_profile\nsecond_assumed_profile", result.unwrap());
+}
+
+#[test]
+fn return_profile_from_credentials_if_config_file_not_found() {
+    let config = config::GetConfig {
+        config_path: get_test_data_path("not_existing.config".to_string()),
+        credentials_path: get_test_data_path("get_matching_found_in_credentials_only.credentials".to_string()),
+        copy_keys: get_default_copy_keys(),
+        all: false
+    };
+
+    let result = get::handle(config);
+
+    assert!(result.is_ok());
+    assert_eq!("second_profile", result.unwrap());
+}
+
+#[test]
+fn return_assumed_profile_if_credentials_file_not_found() {
+    let config = config::GetConfig {
+        config_path: get_test_data_path("get_matching_found_in_both.config".to_string()),
+        credentials_path: get_test_data_path("not_existing.credentials".to_string()),
+        copy_keys: get_default_copy_keys(),
+        all: false
+    };
+
+    let result = get::handle(config);
+
+    assert!(result.is_ok());
+    assert_eq!("second_assumed_profile", result.unwrap());
+}
+
+#[test]
+fn return_profile_with_same_session_token_as_default_profile() {
+    let config = config::GetConfig {
+        config_path: get_test_data_path("not_existing.config".to_string()),
+        credentials_path: get_test_data_path("get_temporary_credentials.credentials".to_string()),
+        copy_keys: get_default_copy_keys(),
+        all: false
+    };
+
+    let result = get::handle(config);
+
+    assert_eq!("second_session_profile", result.unwrap());
 }
@@ -0,0 +1,73 @@
+extern crate raws;
+extern crate test_utilities;
+
+use raws::aws_ini::Ini;
+use raws::handlers::history;
+use raws::config;
+use raws::handlers::picker::PickerResult;
+use test_utilities::{ get_test_data_path, get_temp_file_path };
+
+fn get_default_copy_keys() -> Vec<String> {
+    config::DEFAULT_COPY_KEYS.iter().map(|key| key.to_string()).collect()
+}
+
+fn get_history_config(history_path: &str, select: bool) -> config::HistoryConfig {
+    config::HistoryConfig {
+        config_path: get_test_data_path("set.config".to_string()),
+        credentials_path: get_test_data_path("set.credentials".to_string()),
+        history_path: history_path.to_string(),
+        copied_keys_path: format!("{}.copied_keys", history_path),
+        copy_keys: get_default_copy_keys(),
+        select,
+        picker: config::PickerKind::Auto
+    }
+}
+
+fn save_test_history(test_name: &str) -> String {
+    let history_path = get_temp_file_path(test_name);
+    history::save_history(&history_path, &["profile second_assumed_profile".to_string(), "first_profile".to_string()]).unwrap();
+    history_path
+}
+
+#[test]
+fn list_history_with_current_profile_marked_and_profile_prefix_removed() {
+    let history_path = save_test_history("list_history_with_current_profile_marked_and_profile_prefix_removed");
+
+    let result = history::handle(get_history_config(&history_path, false),
+                                 |_, _, _| panic!("fzf should not be called when listing history"),
+                                 |_: Vec<(Ini, String)>| Ok(()));
+
+    assert_eq!("* 0  second_assumed_profile\n  1  first_profile", result.unwrap());
+}
+
+#[test]
+fn set_profile_selected_from_history() {
+    let history_path = save_test_history("set_profile_selected_from_history");
+    let mut profiles_to_choose = vec!();
+    let mut updated_files = vec!();
+
+    let result = history::handle(get_history_config(&history_path, true),
+                                 |profiles, _, _| {
+                                     profiles_to_choose = profiles;
+                                     Ok(PickerResult::Selected("first_profile".to_string()))
+                                 },
+                                 |files: Vec<(Ini, String)>| {
+                                     updated_files.extend(files.into_iter().map(|(file, _)| file));
+                                     Ok(())
+                                 });
+
+    assert_eq!("default aws profile is set to [first_profile]", result.unwrap());
+    assert_eq!(vec!("second_assumed_profile", "first_profile"), profiles_to_choose);
+    assert_eq!(updated_files[1].get_from(Some("default"), "aws_access_key_id"), Some("1"));
+}
+
+#[test]
+fn return_err_if_history_is_empty() {
+    let history_path = get_temp_file_path("return_err_if_history_is_empty");
+
+    let result = history::handle(get_history_config(&history_path, false),
+                                 |_, _, _| Ok(PickerResult::Cancelled),
+                                 |_: Vec<(Ini, String)>| Ok(()));
+
+    assert_eq!("no profile history recorded yet", format!("{}", result.unwrap_err()));
+}
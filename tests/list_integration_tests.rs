@@ -0,0 +1,66 @@
+extern crate raws;
+extern crate serde_json;
+extern crate test_utilities;
+
+use raws::handlers::list;
+use raws::config;
+use test_utilities::{ get_test_data_path };
+
+fn get_default_copy_keys() -> Vec<String> {
+    config::DEFAULT_COPY_KEYS.iter().map(|key| key.to_string()).collect()
+}
+
+fn get_list_config(output: config::OutputFormat) -> config::ListConfig {
+    config::ListConfig {
+        config_path: get_test_data_path("list.config".to_string()),
+        credentials_path: get_test_data_path("list.credentials".to_string()),
+        copy_keys: get_default_copy_keys(),
+        output
+    }
+}
+
+#[test]
+fn list_profiles_from_both_credentials_and_config_as_table() {
+    let result = list::handle(get_list_config(config::OutputFormat::Table));
+
+    let expected = [
+        "   PROFILE                 KIND         REGION          SOURCE_PROFILE  ROLE_ARN",
+        "   first_profile           static       ap-southeast-2  -               -",
+        "   second_profile          static       -               -               -",
+        "   first_assumed_profile   assume-role  us-east-1       first_profile   1",
+        "*  second_assumed_profile  assume-role  -               second_profile  2",
+    ].join("\n");
+    assert_eq!(expected, result.unwrap());
+}
+
+#[test]
+fn list_profiles_as_json() {
+    let result = list::handle(get_list_config(config::OutputFormat::Json));
+
+    let profiles: serde_json::Value = serde_json::from_str(&result.unwrap()).unwrap();
+    let profiles = profiles.as_array().unwrap();
+    assert_eq!(4, profiles.len());
+    assert_eq!("second_assumed_profile", profiles[3]["name"]);
+    assert_eq!("assume-role", profiles[3]["kind"]);
+    assert_eq!("second_profile", profiles[3]["source_profile"]);
+    assert_eq!(true, profiles[3]["current"]);
+    assert_eq!("first_profile", profiles[0]["name"]);
+    assert_eq!("static", profiles[0]["kind"]);
+    assert_eq!("ap-southeast-2", profiles[0]["region"]);
+    assert!(profiles[0]["role_arn"].is_null());
+}
+
+#[test]
+fn list_static_profiles_if_config_file_not_found() {
+    let mut config = get_list_config(config::OutputFormat::Table);
+    config.config_path = get_test_data_path("not_existing.config".to_string());
+
+    let result = list::handle(config);
+
+    let expected = [
+        "   PROFILE         KIND    REGION  SOURCE_PROFILE  ROLE_ARN",
+        "   first_profile   static  -       -               -",
+        "*  second_profile  static  -       -               -",
+    ].join("\n");
+    assert_eq!(expected, result.unwrap());
+}
@@ -0,0 +1,126 @@
+extern crate raws;
+extern crate test_utilities;
+
+use raws::aws_ini::Ini;
+use raws::handlers::{ remove, history };
+use raws::config;
+use raws::error::{ self, RawsError };
+use test_utilities::{ get_test_data_path, get_temp_file_path };
+use std::error::Error;
+
+fn get_default_copy_keys() -> Vec<String> {
+    config::DEFAULT_COPY_KEYS.iter().map(|key| key.to_string()).collect()
+}
+
+fn get_config(profile: &str, cascade: bool) -> config::RemoveConfig {
+    config::RemoveConfig {
+        config_path: get_test_data_path("remove.config".to_string()),
+        credentials_path: get_test_data_path("remove.credentials".to_string()),
+        history_path: get_temp_file_path("remove_without_history"),
+        copied_keys_path: get_temp_file_path("remove_without_copied_keys"),
+        copy_keys: get_default_copy_keys(),
+        profile: profile.to_string(),
+        cascade,
+    }
+}
+
+fn execute_handle(config: config::RemoveConfig) -> (Result<String, Box<dyn Error>>, Vec<Ini>) {
+    let mut updated_files: Vec<Ini> = vec!();
+
+    let result = {
+        let write_to_files = |files: Vec<(Ini, String)>| {
+            updated_files.extend(files.into_iter().map(|(file, _)| file));
+            Ok(())
+        };
+
+        remove::handle(config, write_to_files)
+    };
+
+    (result, updated_files)
+}
+
+#[test]
+fn remove_profile_that_is_not_referenced() {
+    let (result, updated_files) = execute_handle(get_config("second_role", false));
+
+    assert_eq!("aws profile [second_role] is removed", result.unwrap());
+    let updated_config_file = &updated_files[0];
+    assert!(updated_config_file.section(Some("profile second_role")).is_none());
+    assert!(updated_config_file.section(Some("profile first_role")).is_some());
+    assert_eq!(Some("static_profile"), updated_config_file.get_from(Some("default"), "source_profile"));
+}
+
+#[test]
+fn return_err_if_profile_is_source_profile_of_other_profiles() {
+    let (result, updated_files) = execute_handle(get_config("Other_Profile", false));
+
+    let error = result.unwrap_err();
+    assert_eq!("profile [Other_Profile] is source profile of [other_role], use --cascade to remove them too", error.to_string());
+    assert_eq!(error::EXIT_INVALID_INPUT, error::exit_code_of(error.as_ref()));
+    assert!(updated_files.is_empty());
+}
+
+#[test]
+fn remove_profiles_using_profile_with_cascade_and_role_of_default_profile() {
+    let (result, updated_files) = execute_handle(get_config("static_profile", true));
+
+    let expected = [
+        "aws profiles [static_profile], [first_role], [second_role] are removed",
+        "role settings are removed from default profile since it used a removed profile",
+    ].join("\n");
+    assert_eq!(expected, result.unwrap());
+    let (updated_config_file, updated_credentials_file) = (&updated_files[0], &updated_files[1]);
+    assert!(updated_config_file.section(Some("profile first_role")).is_none());
+    assert!(updated_config_file.section(Some("profile second_role")).is_none());
+    assert!(updated_config_file.section(Some("profile other_role")).is_some());
+    assert!(!updated_config_file.to_string().contains("# chained through first_role"));
+    assert!(updated_config_file.get_from(Some("default"), "role_arn").is_none());
+    assert!(updated_config_file.get_from(Some("default"), "source_profile").is_none());
+    assert_eq!(Some("us-east-1"), updated_config_file.get_from(Some("default"), "region"));
+    assert!(updated_credentials_file.section(Some("Static_Profile")).is_none());
+    assert!(updated_credentials_file.section(Some("other_profile")).is_some());
+}
+
+#[test]
+fn remove_role_settings_of_default_profile_if_it_is_set_to_removed_profile() {
+    let (result, updated_files) = execute_handle(get_config("first_role", true));
+
+    assert!(result.unwrap().ends_with("role settings are removed from default profile since it used a removed profile"));
+    assert!(updated_files[0].get_from(Some("default"), "role_arn").is_none());
+}
+
+#[test]
+fn put_back_values_replaced_by_copied_keys_if_role_settings_of_default_profile_are_removed() {
+    let mut config = get_config("first_role", true);
+    config.copied_keys_path = get_temp_file_path("put_back_values_replaced_by_copied_keys_if_role_settings_of_default_profile_are_removed");
+    std::fs::write(&config.copied_keys_path, "[copied]\nregion = us-east-1\n\n[replaced]\nregion = eu-west-2\n").unwrap();
+
+    let (result, updated_files) = execute_handle(config);
+
+    assert!(result.is_ok());
+    assert_eq!(Some("eu-west-2"), updated_files[0].get_from(Some("default"), "region"));
+    assert_eq!("", updated_files[2].to_string());
+}
+
+#[test]
+fn return_err_if_profile_not_found() {
+    let (result, _) = execute_handle(get_config("not_existing", false));
+
+    match result.unwrap_err().downcast_ref::<RawsError>() {
+        Some(RawsError::ProfileNotFound { profile }) => assert_eq!("not_existing", profile),
+        other => panic!("unexpected error {:?}", other)
+    }
+}
+
+#[test]
+fn remove_removed_profiles_from_history() {
+    let mut config = get_config("static_profile", true);
+    config.history_path = get_temp_file_path("remove_removed_profiles_from_history");
+    history::save_history(&config.history_path, &["second_role".to_string(), "other_role".to_string(), "Static_Profile".to_string()]).unwrap();
+    let history_path = config.history_path.clone();
+
+    let (result, _) = execute_handle(config);
+
+    assert!(result.is_ok());
+    assert_eq!(vec!("other_role"), history::load_history(&history_path).unwrap());
+}
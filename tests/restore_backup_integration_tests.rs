@@ -0,0 +1,127 @@
+extern crate raws;
+extern crate test_utilities;
+
+use raws::handlers::{ restore_backup, history };
+use raws::file_transaction;
+use raws::config;
+use std::fs;
+use test_utilities::{ get_temp_file_path };
+
+fn get_config(test_name: &str) -> config::RestoreBackupConfig {
+    config::RestoreBackupConfig {
+        config_path: get_temp_file_path(&format!("{}.config", test_name)),
+        credentials_path: get_temp_file_path(&format!("{}.credentials", test_name)),
+        history_path: get_temp_file_path(&format!("{}.history", test_name)),
+        timestamp: None,
+        list: false,
+    }
+}
+
+// get_temp_file_path removes existing files, so a config used after files are written is copied instead of created again
+fn copy_config(config: &config::RestoreBackupConfig) -> config::RestoreBackupConfig {
+    config::RestoreBackupConfig {
+        config_path: config.config_path.clone(),
+        credentials_path: config.credentials_path.clone(),
+        history_path: config.history_path.clone(),
+        timestamp: config.timestamp.clone(),
+        list: config.list,
+    }
+}
+
+fn write_files(config: &config::RestoreBackupConfig, config_content: &str, credentials_content: &str) {
+    file_transaction::write_files(vec!(
+        (config_content.to_string(), config.config_path.clone()),
+        (credentials_content.to_string(), config.credentials_path.clone()),
+    )).unwrap();
+}
+
+#[test]
+fn restore_both_files_from_latest_backup() {
+    let config = get_config("restore_both_files_from_latest_backup");
+    fs::write(&config.config_path, "# old config").unwrap();
+    fs::write(&config.credentials_path, "# old credentials").unwrap();
+    write_files(&config, "# new config", "# new credentials");
+    let (config_path, credentials_path) = (config.config_path.clone(), config.credentials_path.clone());
+
+    let result = restore_backup::handle(config);
+
+    assert!(result.unwrap().starts_with("restored backup"));
+    assert_eq!("# old config", fs::read_to_string(&config_path).unwrap());
+    assert_eq!("# old credentials", fs::read_to_string(&credentials_path).unwrap());
+}
+
+#[test]
+fn keep_older_backups_reachable_if_restored_twice() {
+    let config = get_config("keep_older_backups_reachable_if_restored_twice");
+    fs::write(&config.config_path, "# first config").unwrap();
+    write_files(&config, "# second config", "# second credentials");
+    write_files(&config, "# third config", "# third credentials");
+    let timestamps = file_transaction::find_backup_timestamps(&config.config_path);
+    let config_path = config.config_path.clone();
+
+    restore_backup::handle(copy_config(&config)).unwrap();
+    restore_backup::handle(copy_config(&config)).unwrap();
+
+    assert_eq!("# second config", fs::read_to_string(&config_path).unwrap());
+    assert_eq!(timestamps, file_transaction::find_backup_timestamps(&config_path));
+
+    let mut older_backup_config = copy_config(&config);
+    older_backup_config.timestamp = Some(timestamps[1].to_string());
+    let result = restore_backup::handle(older_backup_config);
+
+    assert_eq!(format!("restored backup {} of {}", timestamps[1], config_path), result.unwrap());
+    assert_eq!("# first config", fs::read_to_string(&config_path).unwrap());
+}
+
+#[test]
+fn list_backups_newest_first() {
+    let config = get_config("list_backups_newest_first");
+    fs::write(&config.config_path, "# first config").unwrap();
+    write_files(&config, "# second config", "# second credentials");
+    write_files(&config, "# third config", "# third credentials");
+    let timestamps = file_transaction::find_backup_timestamps(&config.config_path);
+
+    let mut list_config = copy_config(&config);
+    list_config.list = true;
+    let result = restore_backup::handle(list_config);
+
+    let expected = [
+        format!("{}  {}, {}", timestamps[0], config.config_path, config.credentials_path),
+        format!("{}  {}", timestamps[1], config.config_path),
+    ].join("\n");
+    assert_eq!(expected, result.unwrap());
+}
+
+#[test]
+fn return_err_if_backup_with_timestamp_is_not_found() {
+    let config = get_config("return_err_if_backup_with_timestamp_is_not_found");
+    fs::write(&config.config_path, "# old config").unwrap();
+    write_files(&config, "# new config", "# new credentials");
+
+    let mut not_found_config = copy_config(&config);
+    not_found_config.timestamp = Some("1".to_string());
+    let result = restore_backup::handle(not_found_config);
+
+    assert_eq!("invalid timestamp: no backup found with timestamp 1", result.unwrap_err().to_string());
+    assert_eq!("# new config", fs::read_to_string(&config.config_path).unwrap());
+}
+
+#[test]
+fn remove_profiles_missing_from_restored_files_from_history() {
+    let config = get_config("remove_profiles_missing_from_restored_files_from_history");
+    fs::write(&config.credentials_path, "[first]\naws_access_key_id = 1\naws_secret_access_key = 1\n").unwrap();
+    write_files(&config, "", "[second]\naws_access_key_id = 2\naws_secret_access_key = 2\n");
+    history::save_history(&config.history_path, &["second".to_string(), "first".to_string()]).unwrap();
+    let history_path = config.history_path.clone();
+
+    restore_backup::handle(copy_config(&config)).unwrap();
+
+    assert_eq!(vec!("first"), history::load_history(&history_path).unwrap());
+}
+
+#[test]
+fn return_err_if_there_is_no_backup() {
+    let result = restore_backup::handle(get_config("return_err_if_there_is_no_backup"));
+
+    assert_eq!("no backup found", format!("{}", result.unwrap_err()));
+}
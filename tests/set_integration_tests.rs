@@ -1,62 +1,81 @@
-extern crate ini;
 extern crate raws;
 extern crate test_utilities;
 
-use ini::Ini;
-use raws::handlers::set;
+use raws::aws_ini::Ini;
+use raws::handlers::{ set, history };
+use raws::handlers::picker::PickerResult;
 use raws::config;
-use test_utilities::{ get_test_data_path };
+use raws::error;
+use test_utilities::{ get_test_data_path, get_temp_file_path };
 use std::error::Error;
+use std::fs;
 
-fn execute_handle(config: config::SetConfig, chosen_profile: String) -> (Result<String, Box<Error>>, Vec<String>, Vec<Ini>) {
+fn get_default_copy_keys() -> Vec<String> {
+    config::DEFAULT_COPY_KEYS.iter().map(|key| key.to_string()).collect()
+}
+
+type HandleResult = (Result<String, Box<dyn Error>>, Vec<String>, Vec<Ini>);
+
+fn execute_handle(config: config::SetConfig, picker_result: PickerResult) -> HandleResult {
     let mut profiles_to_choose: Vec<String> = Vec::new();
     let mut updated_files: Vec<Ini> = vec!();
 
     let result = {
-        let choose_profile = |profiles: Vec<String>| {
+        let choose_profile = |profiles: Vec<String>, _: &Ini, _: &Ini| {
             profiles_to_choose = profiles;
-            Ok(chosen_profile.clone())
+            Ok(picker_result.clone())
         };
 
-        let write_to_file = |file: Ini, _: &String| {
-            updated_files.push(file);
+        let write_to_files = |files: Vec<(Ini, String)>| {
+            updated_files.extend(files.into_iter().map(|(file, _)| file));
             Ok(())
         };
 
-        set::handle(config, choose_profile, write_to_file)
+        set::handle(config, choose_profile, write_to_files)
     };
 
     (result, profiles_to_choose, updated_files)
 }
 
 #[test]
-fn return_err_if_config_file_not_found() {
+fn set_static_profile_if_config_file_not_found() {
     let config = config::SetConfig {
         config_path: get_test_data_path("not_existing.config".to_string()),
         credentials_path: get_test_data_path("set.credentials".to_string()),
-        pattern: "".to_string()
+        history_path: get_temp_file_path("set_static_profile_if_config_file_not_found"),
+        copied_keys_path: get_temp_file_path("set_static_profile_if_config_file_not_found.copied_keys"),
+        copy_keys: get_default_copy_keys(),
+        pattern: "".to_string(),
+        picker: config::PickerKind::Auto
     };
 
-    let (result,  _, _) = execute_handle(config, "".to_string());
+    let (result, profiles_to_choose, updated_files) = execute_handle(config, PickerResult::Selected("first_profile".to_string()));
 
-    let error_message = format!("{}", result.unwrap_err());
-    assert!(error_message.contains("failed to load file"));
-    assert!(error_message.contains("not_existing.config"));
+    assert_eq!("default aws profile is set to [first_profile]", result.unwrap());
+    assert_eq!(vec!("first_profile", "second_profile"), profiles_to_choose);
+    let updated_credentials_file = &updated_files[1];
+    assert_eq!(updated_credentials_file.get_from(Some("default"), "aws_access_key_id"), Some("1"));
+    assert_eq!(updated_credentials_file.get_from(Some("default"), "aws_secret_access_key"), Some("1"));
 }
 
 #[test]
-fn return_err_if_credentials_file_not_found() {
+fn set_assume_profile_if_credentials_file_not_found() {
     let config = config::SetConfig {
         config_path: get_test_data_path("set.config".to_string()),
         credentials_path: get_test_data_path("not_existing.credentials".to_string()),
-        pattern: "".to_string()
+        history_path: get_temp_file_path("set_assume_profile_if_credentials_file_not_found"),
+        copied_keys_path: get_temp_file_path("set_assume_profile_if_credentials_file_not_found.copied_keys"),
+        copy_keys: get_default_copy_keys(),
+        pattern: "".to_string(),
+        picker: config::PickerKind::Auto
     };
 
-    let (result,  _, _) = execute_handle(config, "".to_string());
+    let (result, _, updated_files) = execute_handle(config, PickerResult::Selected("first_assumed_profile".to_string()));
 
-    let error_message = format!("{}", result.unwrap_err());
-    assert!(error_message.contains("failed to load file"));
-    assert!(error_message.contains("not_existing.credentials"));
+    assert_eq!("default aws profile is set to [first_assumed_profile]", result.unwrap());
+    let updated_config_file = &updated_files[0];
+    assert_eq!(updated_config_file.get_from(Some("default"), "role_arn"), Some("1"));
+    assert_eq!(updated_config_file.get_from(Some("default"), "source_profile"), Some("first_profile"));
 }
 
 #[test]
@@ -64,15 +83,19 @@ fn call_fzf_with_profile_names_from_both_config_and_credentials() {
     let config = config::SetConfig {
         config_path: get_test_data_path("set.config".to_string()),
         credentials_path: get_test_data_path("set.credentials".to_string()),
-        pattern: "".to_string()
+        history_path: get_temp_file_path("call_fzf_with_profile_names_from_both_config_and_credentials"),
+        copied_keys_path: get_temp_file_path("call_fzf_with_profile_names_from_both_config_and_credentials.copied_keys"),
+        copy_keys: get_default_copy_keys(),
+        pattern: "".to_string(),
+        picker: config::PickerKind::Auto
     };
 
-    let (_, profiles_to_choose, _) = execute_handle(config, "".to_string());
+    let (_, profiles_to_choose, _) = execute_handle(config, PickerResult::Cancelled);
     let expected_profiles = vec![
         "first_profile".to_string(),
         "second_profile".to_string(),
-        "profile first_assumed_profile".to_string(),
-        "profile second_assumed_profile".to_string(),
+        "first_assumed_profile".to_string(),
+        "second_assumed_profile".to_string(),
     ];
     assert_eq!(profiles_to_choose, expected_profiles);
 }
@@ -82,14 +105,18 @@ fn set_config_file_default_section_if_selected_profile_can_be_found_in_config()
     let config = config::SetConfig {
         config_path: get_test_data_path("set.config".to_string()),
         credentials_path: get_test_data_path("set.credentials".to_string()),
-        pattern: "".to_string()
+        history_path: get_temp_file_path("set_config_file_default_section_if_selected_profile_can_be_found_in_config"),
+        copied_keys_path: get_temp_file_path("set_config_file_default_section_if_selected_profile_can_be_found_in_config.copied_keys"),
+        copy_keys: get_default_copy_keys(),
+        pattern: "".to_string(),
+        picker: config::PickerKind::Auto
     };
 
-    let (_, _, updated_files) = execute_handle(config, "profile first_assumed_profile".to_string());
+    let (_, _, updated_files) = execute_handle(config, PickerResult::Selected("first_assumed_profile".to_string()));
 
     let updated_config_file = &updated_files[0];
     assert_eq!(updated_config_file.get_from(Some("default"), "role_arn"), Some("1"));
-    assert_eq!(updated_config_file.get_from(Some("default"), "source_profile"), Some("1"));
+    assert_eq!(updated_config_file.get_from(Some("default"), "source_profile"), Some("first_profile"));
 }
 
 #[test]
@@ -97,32 +124,99 @@ fn set_credentials_file_default_section_if_selected_profile_can_only_be_found_in
     let config = config::SetConfig {
         config_path: get_test_data_path("set.config".to_string()),
         credentials_path: get_test_data_path("set.credentials".to_string()),
-        pattern: "".to_string()
+        history_path: get_temp_file_path("set_credentials_file_default_section_if_selected_profile_can_only_be_found_in_credentials"),
+        copied_keys_path: get_temp_file_path("set_credentials_file_default_section_if_selected_profile_can_only_be_found_in_credentials.copied_keys"),
+        copy_keys: get_default_copy_keys(),
+        pattern: "".to_string(),
+        picker: config::PickerKind::Auto
     };
 
-    let (_, _, updated_files) = execute_handle(config, "first_profile".to_string());
+    let (_, _, updated_files) = execute_handle(config, PickerResult::Selected("first_profile".to_string()));
 
     assert_eq!(2, updated_files.len());
 
-    // assert that config file default section is reset
+    // assert that assume role settings are removed from config file default section
     let updated_config_file = &updated_files[0];
-    assert_eq!(updated_config_file.get_from(Some("default"), "role_arn"), Some(""));
-    assert_eq!(updated_config_file.get_from(Some("default"), "source_profile"), Some(""));
+    assert_eq!(updated_config_file.get_from(Some("default"), "role_arn"), None);
+    assert_eq!(updated_config_file.get_from(Some("default"), "source_profile"), None);
 
     let updated_credentials_file = &updated_files[1];
     assert_eq!(updated_credentials_file.get_from(Some("default"), "aws_access_key_id"), Some("1"));
     assert_eq!(updated_credentials_file.get_from(Some("default"), "aws_secret_access_key"), Some("1"));
 }
 
+#[test]
+fn copy_session_token_and_expiry_together_with_keys() {
+    let config = config::SetConfig {
+        config_path: get_test_data_path("not_existing.config".to_string()),
+        credentials_path: get_test_data_path("set_temporary.credentials".to_string()),
+        history_path: get_temp_file_path("copy_session_token_and_expiry_together_with_keys"),
+        copied_keys_path: get_temp_file_path("copy_session_token_and_expiry_together_with_keys.copied_keys"),
+        copy_keys: get_default_copy_keys(),
+        pattern: "session_profile".to_string(),
+        picker: config::PickerKind::Auto
+    };
+
+    let (_, _, updated_files) = execute_handle(config, PickerResult::Cancelled);
+
+    let updated_credentials_file = &updated_files[1];
+    assert_eq!(updated_credentials_file.get_from(Some("default"), "aws_access_key_id"), Some("ASIA3"));
+    assert_eq!(updated_credentials_file.get_from(Some("default"), "aws_session_token"), Some("new_token"));
+    assert_eq!(updated_credentials_file.get_from(Some("default"), "x_security_token_expires"), Some("2026-10-18T10:00:00Z"));
+}
+
+#[test]
+fn remove_session_token_of_previous_profile_if_selected_profile_has_none() {
+    let config = config::SetConfig {
+        config_path: get_test_data_path("not_existing.config".to_string()),
+        credentials_path: get_test_data_path("set_temporary.credentials".to_string()),
+        history_path: get_temp_file_path("remove_session_token_of_previous_profile_if_selected_profile_has_none"),
+        copied_keys_path: get_temp_file_path("remove_session_token_of_previous_profile_if_selected_profile_has_none.copied_keys"),
+        copy_keys: get_default_copy_keys(),
+        pattern: "static_profile".to_string(),
+        picker: config::PickerKind::Auto
+    };
+
+    let (_, _, updated_files) = execute_handle(config, PickerResult::Cancelled);
+
+    let updated_credentials_file = &updated_files[1];
+    assert_eq!(updated_credentials_file.get_from(Some("default"), "aws_access_key_id"), Some("2"));
+    assert_eq!(updated_credentials_file.get_from(Some("default"), "aws_session_token"), None);
+    assert_eq!(updated_credentials_file.get_from(Some("default"), "x_security_token_expires"), None);
+}
+
+#[test]
+fn return_error_result_if_source_profile_of_selected_profile_is_not_found() {
+    let config = config::SetConfig {
+        config_path: get_test_data_path("set_broken.config".to_string()),
+        credentials_path: get_test_data_path("set.credentials".to_string()),
+        history_path: get_temp_file_path("return_error_result_if_source_profile_of_selected_profile_is_not_found"),
+        copied_keys_path: get_temp_file_path("return_error_result_if_source_profile_of_selected_profile_is_not_found.copied_keys"),
+        copy_keys: get_default_copy_keys(),
+        pattern: "broken_assumed_profile".to_string(),
+        picker: config::PickerKind::Auto
+    };
+
+    let (result, _, updated_files) = execute_handle(config, PickerResult::Cancelled);
+
+    assert_eq!(0, updated_files.len());
+    let error_message = format!("{}", result.unwrap_err());
+    assert_eq!("source profile [removed_profile] of profile [broken_assumed_profile] not found in both config and credentials file", error_message);
+}
+
 #[test]
 fn return_error_result_if_profile_is_not_in_both_config_and_credentials() {
     let config = config::SetConfig {
         config_path: get_test_data_path("set.config".to_string()),
         credentials_path: get_test_data_path("set.credentials".to_string()),
-        pattern: "".to_string()
+        history_path: get_temp_file_path("return_error_result_if_profile_is_not_in_both_config_and_credentials"),
+        copied_keys_path: get_temp_file_path("return_error_result_if_profile_is_not_in_both_config_and_credentials.copied_keys"),
+        copy_keys: get_default_copy_keys(),
+        pattern: "".to_string(),
+        picker: config::PickerKind::Auto
     };
 
-    let (result, _, updated_files) = execute_handle(config, "third_profile".to_string());
+    let (result, _, updated_files) = execute_handle(config, PickerResult::Selected("third_profile".to_string()));
 
     assert_eq!(0, updated_files.len());
     let error_message = format!("{}", result.unwrap_err());
@@ -134,12 +228,312 @@ fn return_early_if_select_profiles_action_is_cancelled() {
     let config = config::SetConfig {
         config_path: get_test_data_path("set.config".to_string()),
         credentials_path: get_test_data_path("set.credentials".to_string()),
-        pattern: "".to_string()
+        history_path: get_temp_file_path("return_early_if_select_profiles_action_is_cancelled"),
+        copied_keys_path: get_temp_file_path("return_early_if_select_profiles_action_is_cancelled.copied_keys"),
+        copy_keys: get_default_copy_keys(),
+        pattern: "".to_string(),
+        picker: config::PickerKind::Auto
     };
 
-    // when user presses Ctrl-C during fzf selection, chosen_profile is empty string
-    let (result, _, updated_files) = execute_handle(config, "".to_string());
+    // user pressed Esc or Ctrl-C during selection
+    let (result, _, updated_files) = execute_handle(config, PickerResult::Cancelled);
 
     assert_eq!(0, updated_files.len());
+    assert_eq!(error::EXIT_PICKER_CANCELLED, error::exit_code_of(result.unwrap_err().as_ref()));
+}
+
+#[test]
+fn call_fzf_if_pattern_does_not_match_any_profile_exactly() {
+    let config = config::SetConfig {
+        config_path: get_test_data_path("set.config".to_string()),
+        credentials_path: get_test_data_path("set.credentials".to_string()),
+        history_path: get_temp_file_path("call_fzf_if_pattern_does_not_match_any_profile_exactly"),
+        copied_keys_path: get_temp_file_path("call_fzf_if_pattern_does_not_match_any_profile_exactly.copied_keys"),
+        copy_keys: get_default_copy_keys(),
+        pattern: "first".to_string(),
+        picker: config::PickerKind::Auto
+    };
+
+    let (_, profiles_to_choose, _) = execute_handle(config, PickerResult::Selected("first_profile".to_string()));
+
+    assert_eq!(4, profiles_to_choose.len());
+}
+
+#[test]
+fn set_profile_without_calling_fzf_if_pattern_matches_profile_exactly() {
+    let config = config::SetConfig {
+        config_path: get_test_data_path("set.config".to_string()),
+        credentials_path: get_test_data_path("set.credentials".to_string()),
+        history_path: get_temp_file_path("set_profile_without_calling_fzf_if_pattern_matches_profile_exactly"),
+        copied_keys_path: get_temp_file_path("set_profile_without_calling_fzf_if_pattern_matches_profile_exactly.copied_keys"),
+        copy_keys: get_default_copy_keys(),
+        pattern: "PROFILE First_Assumed_Profile".to_string(),
+        picker: config::PickerKind::Auto
+    };
+
+    let (result, profiles_to_choose, updated_files) = execute_handle(config, PickerResult::Cancelled);
+
+    assert!(profiles_to_choose.is_empty());
+    assert_eq!("default aws profile is set to [first_assumed_profile]", result.unwrap());
+    let updated_config_file = &updated_files[0];
+    assert_eq!(updated_config_file.get_from(Some("default"), "role_arn"), Some("1"));
+    assert_eq!(updated_config_file.get_from(Some("default"), "source_profile"), Some("first_profile"));
+}
+
+#[test]
+fn record_previous_and_selected_profiles_in_history() {
+    let history_path = get_temp_file_path("record_previous_and_selected_profiles_in_history");
+    let config = config::SetConfig {
+        config_path: get_test_data_path("set.config".to_string()),
+        credentials_path: get_test_data_path("set.credentials".to_string()),
+        history_path: history_path.clone(),
+        copied_keys_path: get_temp_file_path("record_previous_and_selected_profiles_in_history.copied_keys"),
+        copy_keys: get_default_copy_keys(),
+        pattern: "".to_string(),
+        picker: config::PickerKind::Auto
+    };
+
+    let (result, _, _) = execute_handle(config, PickerResult::Selected("first_profile".to_string()));
+
     assert!(result.is_ok());
-}
\ No newline at end of file
+    let history = history::load_history(&history_path).unwrap();
+    assert_eq!(vec!("first_profile", "second_assumed_profile"), history);
+}
+
+#[test]
+fn warn_instead_of_failing_if_history_can_not_be_saved_after_default_profile_is_set() {
+    let config = config::SetConfig {
+        config_path: get_test_data_path("set.config".to_string()),
+        credentials_path: get_test_data_path("set.credentials".to_string()),
+        history_path: format!("{}/history", get_temp_file_path("missing_history_directory")),
+        copied_keys_path: get_temp_file_path("warn_instead_of_failing_if_history_can_not_be_saved_after_default_profile_is_set.copied_keys"),
+        copy_keys: get_default_copy_keys(),
+        pattern: "".to_string(),
+        picker: config::PickerKind::Auto
+    };
+
+    let (result, _, updated_files) = execute_handle(config, PickerResult::Selected("first_profile".to_string()));
+
+    let message = result.unwrap();
+    assert!(message.starts_with("default aws profile is set to [first_profile]\nwarning: profile history is not updated: "));
+    assert!(!updated_files.is_empty());
+}
+
+#[test]
+fn set_previous_profile_from_history_without_calling_fzf_if_pattern_is_dash() {
+    let history_path = get_temp_file_path("set_previous_profile_from_history_without_calling_fzf_if_pattern_is_dash");
+    history::save_history(&history_path, &["profile second_assumed_profile".to_string(), "first_profile".to_string()]).unwrap();
+    let config = config::SetConfig {
+        config_path: get_test_data_path("set.config".to_string()),
+        credentials_path: get_test_data_path("set.credentials".to_string()),
+        history_path: history_path.clone(),
+        copied_keys_path: get_temp_file_path("set_previous_profile_from_history_without_calling_fzf_if_pattern_is_dash.copied_keys"),
+        copy_keys: get_default_copy_keys(),
+        pattern: "-".to_string(),
+        picker: config::PickerKind::Auto
+    };
+
+    let (result, profiles_to_choose, updated_files) = execute_handle(config, PickerResult::Cancelled);
+
+    assert!(profiles_to_choose.is_empty());
+    assert_eq!("default aws profile is set to [first_profile]", result.unwrap());
+    let updated_credentials_file = &updated_files[1];
+    assert_eq!(updated_credentials_file.get_from(Some("default"), "aws_access_key_id"), Some("1"));
+}
+
+#[test]
+fn return_err_if_pattern_is_dash_and_there_is_no_previous_profile_in_history() {
+    let config = config::SetConfig {
+        config_path: get_test_data_path("set.config".to_string()),
+        credentials_path: get_test_data_path("set.credentials".to_string()),
+        history_path: get_temp_file_path("return_err_if_pattern_is_dash_and_there_is_no_previous_profile_in_history"),
+        copied_keys_path: get_temp_file_path("return_err_if_pattern_is_dash_and_there_is_no_previous_profile_in_history.copied_keys"),
+        copy_keys: get_default_copy_keys(),
+        pattern: "-".to_string(),
+        picker: config::PickerKind::Auto
+    };
+
+    let (result, _, updated_files) = execute_handle(config, PickerResult::Cancelled);
+
+    assert_eq!(0, updated_files.len());
+    let error_message = format!("{}", result.unwrap_err());
+    assert_eq!("no previous profile found in history", error_message);
+}
+
+#[test]
+fn only_change_default_section_lines_and_keep_comments_and_formatting() {
+    let config = config::SetConfig {
+        config_path: get_test_data_path("set_formatted.config".to_string()),
+        credentials_path: get_test_data_path("set.credentials".to_string()),
+        history_path: get_temp_file_path("only_change_default_section_lines_and_keep_comments_and_formatting"),
+        copied_keys_path: get_temp_file_path("only_change_default_section_lines_and_keep_comments_and_formatting.copied_keys"),
+        copy_keys: get_default_copy_keys(),
+        pattern: "".to_string(),
+        picker: config::PickerKind::Auto
+    };
+
+    let (_, _, updated_files) = execute_handle(config, PickerResult::Selected("first_assumed_profile".to_string()));
+
+    let original = fs::read_to_string(get_test_data_path("set_formatted.config".to_string())).unwrap();
+    let expected = original.replacen("role_arn = 2\nsource_profile = second_profile\n", "role_arn = 1\nsource_profile = first_profile\n", 1);
+    assert_eq!(expected, updated_files[0].to_string());
+}
+
+#[test]
+fn keep_nested_blocks_of_default_section_when_adding_assume_settings() {
+    let config = config::SetConfig {
+        config_path: get_test_data_path("set_nested.config".to_string()),
+        credentials_path: get_test_data_path("set.credentials".to_string()),
+        history_path: get_temp_file_path("keep_nested_blocks_of_default_section_when_adding_assume_settings"),
+        copied_keys_path: get_temp_file_path("keep_nested_blocks_of_default_section_when_adding_assume_settings.copied_keys"),
+        copy_keys: get_default_copy_keys(),
+        pattern: "".to_string(),
+        picker: config::PickerKind::Auto
+    };
+
+    let (_, _, updated_files) = execute_handle(config, PickerResult::Selected("first_assumed_profile".to_string()));
+
+    let original = fs::read_to_string(get_test_data_path("set_nested.config".to_string())).unwrap();
+    let expected = original.replacen("  addressing_style = path\n", "  addressing_style = path\nrole_arn = 1\nsource_profile = first_profile\n", 1);
+    let updated_config_file = &updated_files[0];
+    assert_eq!(expected, updated_config_file.to_string());
+    let s3 = updated_config_file.get_nested_from(Some("default"), "s3").unwrap();
+    assert_eq!(Some(&"20".to_string()), s3.get("max_concurrent_requests"));
+}
+
+#[test]
+fn remove_all_assume_role_keys_from_config_file_default_section_if_selected_profile_is_in_credentials_only() {
+    let config = config::SetConfig {
+        config_path: get_test_data_path("set_mfa.config".to_string()),
+        credentials_path: get_test_data_path("set.credentials".to_string()),
+        history_path: get_temp_file_path("remove_all_assume_role_keys_from_config_file_default_section_if_selected_profile_is_in_credentials_only"),
+        copied_keys_path: get_temp_file_path("remove_all_assume_role_keys_from_config_file_default_section_if_selected_profile_is_in_credentials_only.copied_keys"),
+        copy_keys: get_default_copy_keys(),
+        pattern: "".to_string(),
+        picker: config::PickerKind::Auto
+    };
+
+    let (_, _, updated_files) = execute_handle(config, PickerResult::Selected("first_profile".to_string()));
+
+    let expected = [
+        "[default]",
+        "region = us-east-1",
+        "",
+        "[profile mfa_assumed_profile]",
+        "role_arn = 1",
+        "source_profile = first_profile",
+        "mfa_serial = arn:aws:iam::123456789012:mfa/user",
+        "",
+    ].join("\n");
+    assert_eq!(expected, updated_files[0].to_string());
+}
+
+#[test]
+fn copy_all_configured_keys_of_selected_assume_profile_and_remove_stale_keys() {
+    let config = config::SetConfig {
+        config_path: get_test_data_path("set_mfa.config".to_string()),
+        credentials_path: get_test_data_path("set.credentials".to_string()),
+        history_path: get_temp_file_path("copy_all_configured_keys_of_selected_assume_profile_and_remove_stale_keys"),
+        copied_keys_path: get_temp_file_path("copy_all_configured_keys_of_selected_assume_profile_and_remove_stale_keys.copied_keys"),
+        copy_keys: get_default_copy_keys(),
+        pattern: "".to_string(),
+        picker: config::PickerKind::Auto
+    };
+
+    let (_, _, updated_files) = execute_handle(config, PickerResult::Selected("profile mfa_assumed_profile".to_string()));
+
+    let expected = [
+        "[default]",
+        "region = us-east-1",
+        "role_arn = 1",
+        "source_profile = first_profile",
+        "mfa_serial = arn:aws:iam::123456789012:mfa/user",
+        "",
+        "[profile mfa_assumed_profile]",
+        "role_arn = 1",
+        "source_profile = first_profile",
+        "mfa_serial = arn:aws:iam::123456789012:mfa/user",
+        "",
+    ].join("\n");
+    assert_eq!(expected, updated_files[0].to_string());
+}
+
+#[test]
+fn keep_region_and_output_of_default_profile_if_selected_assume_profile_has_none() {
+    let config = config::SetConfig {
+        config_path: get_test_data_path("set_default_region.config".to_string()),
+        credentials_path: get_test_data_path("set.credentials".to_string()),
+        history_path: get_temp_file_path("keep_region_and_output_of_default_profile_if_selected_assume_profile_has_none"),
+        copied_keys_path: get_temp_file_path("keep_region_and_output_of_default_profile_if_selected_assume_profile_has_none.copied_keys"),
+        copy_keys: get_default_copy_keys(),
+        pattern: "".to_string(),
+        picker: config::PickerKind::Auto
+    };
+
+    let (_, _, updated_files) = execute_handle(config, PickerResult::Selected("role_without_region".to_string()));
+
+    let updated_config_file = &updated_files[0];
+    assert_eq!(Some("2"), updated_config_file.get_from(Some("default"), "role_arn"));
+    assert_eq!(Some("ap-southeast-2"), updated_config_file.get_from(Some("default"), "region"));
+    assert_eq!(Some("json"), updated_config_file.get_from(Some("default"), "output"));
+}
+
+#[test]
+fn record_keys_copied_to_default_profile_with_values_they_replaced() {
+    let config = config::SetConfig {
+        config_path: get_test_data_path("set_default_region.config".to_string()),
+        credentials_path: get_test_data_path("set.credentials".to_string()),
+        history_path: get_temp_file_path("record_keys_copied_to_default_profile_with_values_they_replaced"),
+        copied_keys_path: get_temp_file_path("record_keys_copied_to_default_profile_with_values_they_replaced.copied_keys"),
+        copy_keys: get_default_copy_keys(),
+        pattern: "".to_string(),
+        picker: config::PickerKind::Auto
+    };
+
+    let (_, _, updated_files) = execute_handle(config, PickerResult::Selected("role_with_region".to_string()));
+
+    assert_eq!(Some("eu-west-1"), updated_files[0].get_from(Some("default"), "region"));
+    assert_eq!("[copied]\nregion = eu-west-1\n\n[replaced]\nregion = ap-southeast-2\n", updated_files[2].to_string());
+}
+
+#[test]
+fn put_back_region_replaced_by_previous_assume_profile_if_selected_profile_is_in_credentials_only() {
+    let config = config::SetConfig {
+        config_path: get_test_data_path("set_copied_region.config".to_string()),
+        credentials_path: get_test_data_path("set.credentials".to_string()),
+        history_path: get_temp_file_path("put_back_region_replaced_by_previous_assume_profile_if_selected_profile_is_in_credentials_only"),
+        copied_keys_path: get_temp_file_path("put_back_region_replaced_by_previous_assume_profile_if_selected_profile_is_in_credentials_only.copied_keys"),
+        copy_keys: get_default_copy_keys(),
+        pattern: "".to_string(),
+        picker: config::PickerKind::Auto
+    };
+    fs::write(&config.copied_keys_path, "[copied]\nregion = eu-west-1\n\n[replaced]\nregion = ap-southeast-2\n").unwrap();
+
+    let (_, _, updated_files) = execute_handle(config, PickerResult::Selected("second_profile".to_string()));
+
+    let updated_config_file = &updated_files[0];
+    assert!(updated_config_file.get_from(Some("default"), "role_arn").is_none());
+    assert_eq!(Some("ap-southeast-2"), updated_config_file.get_from(Some("default"), "region"));
+    assert_eq!(Some("json"), updated_config_file.get_from(Some("default"), "output"));
+    assert_eq!("", updated_files[2].to_string());
+}
+
+#[test]
+fn keep_region_of_default_profile_that_raws_did_not_copy_if_selected_profile_is_in_credentials_only() {
+    let config = config::SetConfig {
+        config_path: get_test_data_path("set_copied_region.config".to_string()),
+        credentials_path: get_test_data_path("set.credentials".to_string()),
+        history_path: get_temp_file_path("keep_region_of_default_profile_that_raws_did_not_copy_if_selected_profile_is_in_credentials_only"),
+        copied_keys_path: get_temp_file_path("keep_region_of_default_profile_that_raws_did_not_copy_if_selected_profile_is_in_credentials_only.copied_keys"),
+        copy_keys: get_default_copy_keys(),
+        pattern: "".to_string(),
+        picker: config::PickerKind::Auto
+    };
+
+    let (_, _, updated_files) = execute_handle(config, PickerResult::Selected("second_profile".to_string()));
+
+    let updated_config_file = &updated_files[0];
+    assert!(updated_config_file.get_from(Some("default"), "role_arn").is_none());
+    assert_eq!(Some("eu-west-1"), updated_config_file.get_from(Some("default"), "region"));
+    assert_eq!(2, updated_files.len());
+}
@@ -0,0 +1,41 @@
+extern crate raws;
+extern crate test_utilities;
+
+use raws::handlers::validate;
+use raws::config;
+use raws::error::{ self, RawsError };
+use test_utilities::{ get_test_data_path };
+
+#[test]
+fn return_number_of_checked_profiles_if_all_profiles_are_valid() {
+    let config = config::ValidateConfig {
+        config_path: get_test_data_path("describe.config".to_string()),
+        credentials_path: get_test_data_path("describe.credentials".to_string()),
+    };
+
+    let result = validate::handle(config);
+
+    assert_eq!("all 4 profiles are valid", result.unwrap());
+}
+
+#[test]
+fn return_err_with_dangling_source_profiles_and_cycles() {
+    let config = config::ValidateConfig {
+        config_path: get_test_data_path("validate.config".to_string()),
+        credentials_path: get_test_data_path("validate.credentials".to_string()),
+    };
+
+    let result = validate::handle(config);
+
+    let error = result.unwrap_err();
+    let expected = [
+        "found 4 invalid profile(s)",
+        "  - source profile [removed_profile] of profile [default] not found in both config and credentials file",
+        "  - source profile [removed_profile] of profile [dangling_assumed_profile] not found in both config and credentials file",
+        "  - source profiles form a cycle: first_cycle_profile -> second_cycle_profile -> first_cycle_profile",
+        "  - source profiles form a cycle: second_cycle_profile -> first_cycle_profile -> second_cycle_profile",
+    ].join("\n");
+    assert_eq!(expected, error.to_string());
+    assert!(matches!(error.downcast_ref::<RawsError>(), Some(RawsError::InvalidProfiles { .. })));
+    assert_eq!(error::EXIT_INVALID_PROFILE, error::exit_code_of(error.as_ref()));
+}